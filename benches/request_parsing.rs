@@ -0,0 +1,124 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use http::{
+    headers::{HeaderName, parser::Parse},
+    request::{parser::RequestParser, request_from_reader},
+};
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    hint::black_box,
+};
+
+const REQUEST: &[u8] = b"POST /coffee HTTP/1.1\r\n\
+Host: localhost:42069\r\n\
+User-Agent: Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0\r\n\
+Accept: text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8\r\n\
+Accept-Language: en-US,en;q=0.5\r\n\
+Accept-Encoding: gzip, deflate, br\r\n\
+Content-Type: application/json\r\n\
+Content-Length: 22\r\n\
+Connection: keep-alive\r\n\
+Cache-Control: no-cache\r\n\
+X-Request-Id: 5c1d0f3e-6e0b-4f59-a8f4-0c5f1b8e2a77\r\n\
+\r\n\
+{\"flavor\":\"dark mode\"}";
+
+fn request_from_reader_benchmark(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("request_from_reader");
+    group.throughput(Throughput::Bytes(REQUEST.len() as u64));
+    group.bench_function("typical_request", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let request = request_from_reader(black_box(REQUEST)).await.unwrap();
+            black_box(request.headers.get("content-type"));
+            black_box(request.headers.get("X-Request-Id"));
+        })
+    });
+    group.finish();
+}
+
+const LOOKUPS: [&str; 6] = [
+    "content-type",
+    "Content-Length",
+    "x-request-id",
+    "connection",
+    "Accept-Encoding",
+    "x-missing",
+];
+
+const NAMES: [HeaderName; 6] = [
+    HeaderName::CONTENT_TYPE,
+    HeaderName::CONTENT_LENGTH,
+    HeaderName::X_REQUEST_ID,
+    HeaderName::CONNECTION,
+    HeaderName::ACCEPT_ENCODING,
+    HeaderName::ORIGIN,
+];
+
+fn lowercase_headers(request: &[u8]) -> HashMap<String, String> {
+    let request = std::str::from_utf8(request).unwrap();
+    let (head, _) = request.split_once("\r\n\r\n").unwrap();
+    let mut headers = HashMap::new();
+    for line in head.split("\r\n").skip(1) {
+        let (field, value) = line.split_once(':').unwrap();
+        let field_normalized = field.trim().to_lowercase();
+        match headers.entry(field_normalized.trim().to_lowercase()) {
+            Entry::Occupied(mut entry) => {
+                let existing: &mut String = entry.get_mut();
+                existing.push_str(", ");
+                existing.push_str(value.trim());
+            }
+            Entry::Vacant(_) => {
+                headers.insert(field_normalized, value.trim().to_string());
+            }
+        }
+    }
+    headers
+}
+
+fn headers_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("headers");
+    group.bench_function("parse_head", |b| {
+        b.iter(|| {
+            let mut parser = RequestParser::new();
+            parser.parse(black_box(REQUEST)).unwrap();
+            black_box(parser.output(REQUEST).unwrap())
+        })
+    });
+
+    group.bench_function("parse_head_lowercase", |b| {
+        b.iter(|| black_box(lowercase_headers(black_box(REQUEST))))
+    });
+
+    let mut parser = RequestParser::new();
+    parser.parse(REQUEST).unwrap();
+    let (_, headers) = parser.output(REQUEST).unwrap();
+    group.bench_function("lookup", |b| {
+        b.iter(|| {
+            for name in LOOKUPS {
+                black_box(headers.get(black_box(name)));
+            }
+        })
+    });
+    let lowercase = lowercase_headers(REQUEST);
+    group.bench_function("lookup_lowercase", |b| {
+        b.iter(|| {
+            for name in LOOKUPS {
+                black_box(lowercase.get(black_box(name).to_lowercase().as_str()));
+            }
+        })
+    });
+    group.bench_function("lookup_by_name", |b| {
+        b.iter(|| {
+            for name in &NAMES {
+                black_box(headers.get(black_box(name)));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, request_from_reader_benchmark, headers_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use http::response::Response;
+use std::hint::black_box;
+use tokio::io::{AsyncWriteExt, sink};
+use tokio::net::UnixStream;
+use tokio::runtime::Runtime;
+
+const SIZES: [usize; 3] = [64 * 1024, 1024 * 1024, 16 * 1024 * 1024];
+
+fn connection(runtime: &Runtime) -> UnixStream {
+    let _guard = runtime.enter();
+    let (writer, mut reader) = UnixStream::pair().unwrap();
+    runtime.spawn(async move { tokio::io::copy(&mut reader, &mut sink()).await });
+    writer
+}
+
+fn response_writing_benchmark(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("response_writing");
+    for size in SIZES {
+        let response = Response::builder()
+            .header("Content-Type", "application/octet-stream")
+            .body(vec![0xa5; size])
+            .unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("send", size), &response, |b, response| {
+            b.to_async(&runtime).iter_batched(
+                || (response.clone(), connection(&runtime)),
+                |(mut response, mut socket)| async move {
+                    let bytes = response.send(None).unwrap();
+                    socket.write_all(black_box(&bytes)).await.unwrap();
+                    socket.flush().await.unwrap();
+                },
+                BatchSize::PerIteration,
+            )
+        });
+        group.bench_with_input(
+            BenchmarkId::new("write_to", size),
+            &response,
+            |b, response| {
+                b.to_async(&runtime).iter_batched(
+                    || (response.clone(), connection(&runtime)),
+                    |(mut response, mut socket)| async move {
+                        response
+                            .write_to(black_box(&mut socket), None, true)
+                            .await
+                            .unwrap();
+                    },
+                    BatchSize::PerIteration,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, response_writing_benchmark);
+criterion_main!(benches);
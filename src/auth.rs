@@ -0,0 +1,7 @@
+pub mod auth;
+pub mod basic;
+pub mod bearer;
+pub mod jwt;
+pub use basic::BasicAuth;
+pub use bearer::BearerAuth;
+pub use jwt::{JwtAuth, VerificationKey};
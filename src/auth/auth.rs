@@ -0,0 +1,90 @@
+use std::future::Future;
+
+use crate::{
+    response::{Response, Status},
+    router::{middleware::BoxFuture, path::normalize},
+};
+
+pub type Verifier<C, P> = Box<dyn Fn(C) -> BoxFuture<'static, Option<P>> + Send + Sync>;
+
+pub fn verifier<C, P, F, Fut>(verify: F) -> Verifier<C, P>
+where
+    F: Fn(C) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<P>> + Send + 'static,
+{
+    Box::new(move |credentials| Box::pin(verify(credentials)))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    prefixes: Vec<String>,
+}
+
+impl Scope {
+    pub fn add(&mut self, prefix: &str) {
+        let prefix = normalize(prefix);
+        self.prefixes.push(prefix.trim_end_matches('/').to_string());
+    }
+
+    pub fn covers(&self, target: &str) -> bool {
+        let path = target.split_once('?').map_or(target, |(path, _)| path);
+        let path = normalize(path);
+        self.prefixes.is_empty()
+            || self.prefixes.iter().any(|prefix| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+    }
+}
+
+pub fn unauthorized(challenge: &str) -> Response {
+    let mut response = Response::new();
+    response.status(Status::UNAUTHORIZED);
+    response.set_header("WWW-Authenticate", challenge).unwrap();
+    response
+}
+
+pub fn bearer_challenge(realm: &str, error: Option<&str>) -> Response {
+    let mut challenge = format!("Bearer realm={}", quote(realm));
+    if let Some(error) = error {
+        challenge.push_str(&format!(", error={}", quote(error)));
+    }
+    unauthorized(&challenge)
+}
+
+pub fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_matches_path_segments() {
+        let mut scope = Scope::default();
+        assert!(scope.covers("/anything"));
+
+        scope.add("/admin/");
+        assert!(scope.covers("/admin"));
+        assert!(scope.covers("/admin/users?page=2"));
+        assert!(!scope.covers("/administrator"));
+        assert!(!scope.covers("/"));
+    }
+
+    #[test]
+    fn scope_normalizes_like_the_router() {
+        let mut scope = Scope::default();
+        scope.add("admin//");
+        assert!(scope.covers("//admin"));
+        assert!(scope.covers("/admin/"));
+        assert!(scope.covers("/admin//users"));
+        assert!(scope.covers("admin?page=2"));
+        assert!(!scope.covers("//administrator"));
+
+        let mut root = Scope::default();
+        root.add("/");
+        assert!(root.covers("/"));
+        assert!(root.covers("//anything"));
+    }
+}
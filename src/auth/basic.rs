@@ -0,0 +1,153 @@
+use std::future::Future;
+
+use crate::{
+    auth::auth::{Scope, Verifier, quote, unauthorized, verifier},
+    headers::typed::Authorization,
+    response::Response,
+    router::{
+        middleware::{BoxFuture, Middleware, Next},
+        router::Context,
+    },
+};
+
+pub struct BasicAuth<P> {
+    realm: String,
+    verifier: Verifier<(String, String), P>,
+    scope: Scope,
+}
+
+impl<P: Send + Sync + 'static> BasicAuth<P> {
+    pub fn new<F, Fut>(realm: &str, verify: F) -> Self
+    where
+        F: Fn(String, String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<P>> + Send + 'static,
+    {
+        Self {
+            realm: realm.to_string(),
+            verifier: verifier(move |(username, password)| verify(username, password)),
+            scope: Scope::default(),
+        }
+    }
+
+    pub fn protect(mut self, prefix: &str) -> Self {
+        self.scope.add(prefix);
+        self
+    }
+
+    fn challenge(&self) -> Response {
+        unauthorized(&format!(
+            "Basic realm={}, charset=\"UTF-8\"",
+            quote(&self.realm)
+        ))
+    }
+}
+
+impl<P: Send + Sync + 'static> Middleware for BasicAuth<P> {
+    fn handle<'a>(&'a self, mut ctx: Context, next: Next<'a>) -> BoxFuture<'a, Response> {
+        Box::pin(async move {
+            if !self.scope.covers(&ctx.request.request_line.request_target) {
+                return next.run(ctx).await;
+            }
+            let Ok(Some(Authorization::Basic { username, password })) =
+                ctx.request.headers.typed_get::<Authorization>()
+            else {
+                return self.challenge();
+            };
+            match (self.verifier)((username, password)).await {
+                Some(principal) => {
+                    ctx.insert(principal);
+                    next.run(ctx).await
+                }
+                None => self.challenge(),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        headers::typed::Header,
+        request::{request::Request, request_from_reader},
+        router::{
+            SlashPolicy,
+            router::{HandlerError, Router},
+        },
+    };
+
+    #[derive(Debug)]
+    struct User(String);
+
+    fn router() -> Router {
+        let mut router = Router::new();
+        router.add_middleware(
+            BasicAuth::new("admin area", |username, password| async move {
+                (password == "secret").then_some(User(username))
+            })
+            .protect("/admin"),
+        );
+        router.get("/admin", |ctx: Context| async move {
+            let User(name) = ctx.get::<User>().ok_or(HandlerError::InternalError)?;
+            let mut response = Response::new();
+            response.body(name.clone().into_bytes());
+            Ok(response)
+        });
+        router.get("/", |ctx: Context| async move { Ok(ctx.response) });
+        router
+    }
+
+    async fn request(target: &str, authorization: Option<&str>) -> Request {
+        let header =
+            authorization.map_or(String::new(), |value| format!("Authorization: {value}\r\n"));
+        let raw = format!("GET {target} HTTP/1.1\r\n{header}\r\n");
+        request_from_reader(raw.as_bytes()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn verifies_basic_credentials() {
+        let router = router();
+        let public = router
+            .handle_request(request("/", None).await, Response::new())
+            .await;
+        assert_eq!(public.status_code(), 200);
+
+        let credentials = Authorization::Basic {
+            username: "alice".to_string(),
+            password: "secret".to_string(),
+        };
+        let admin = router
+            .handle_request(
+                request("/admin", Some(&credentials.encode())).await,
+                Response::new(),
+            )
+            .await;
+        assert_eq!(admin.status_code(), 200);
+        assert_eq!(admin.body_bytes(), b"alice");
+
+        for authorization in [None, Some("Basic YWxpY2U6d3Jvbmc="), Some("Bearer token")] {
+            let denied = router
+                .handle_request(request("/admin", authorization).await, Response::new())
+                .await;
+            assert_eq!(denied.status_code(), 401);
+            assert_eq!(
+                denied.headers().get("www-authenticate"),
+                Some("Basic realm=\"admin area\", charset=\"UTF-8\"")
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn protects_unnormalized_paths() {
+        let mut router = router();
+        for policy in [SlashPolicy::Strict, SlashPolicy::Normalize] {
+            router.set_slash_policy(policy);
+            for target in ["//admin", "/admin/", "/admin//"] {
+                let denied = router
+                    .handle_request(request(target, None).await, Response::new())
+                    .await;
+                assert_eq!(denied.status_code(), 401, "{target}");
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::future::Future;
+
+use crate::{
+    auth::auth::{Scope, Verifier, bearer_challenge, verifier},
+    headers::typed::Authorization,
+    response::Response,
+    router::{
+        middleware::{BoxFuture, Middleware, Next},
+        router::Context,
+    },
+};
+
+pub struct BearerAuth<P> {
+    realm: String,
+    verifier: Verifier<String, P>,
+    scope: Scope,
+}
+
+impl<P: Send + Sync + 'static> BearerAuth<P> {
+    pub fn new<F, Fut>(realm: &str, verify: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<P>> + Send + 'static,
+    {
+        Self {
+            realm: realm.to_string(),
+            verifier: verifier(verify),
+            scope: Scope::default(),
+        }
+    }
+
+    pub fn protect(mut self, prefix: &str) -> Self {
+        self.scope.add(prefix);
+        self
+    }
+
+    fn challenge(&self, error: Option<&str>) -> Response {
+        bearer_challenge(&self.realm, error)
+    }
+}
+
+impl<P: Send + Sync + 'static> Middleware for BearerAuth<P> {
+    fn handle<'a>(&'a self, mut ctx: Context, next: Next<'a>) -> BoxFuture<'a, Response> {
+        Box::pin(async move {
+            if !self.scope.covers(&ctx.request.request_line.request_target) {
+                return next.run(ctx).await;
+            }
+            let token = match ctx.request.headers.typed_get::<Authorization>() {
+                Ok(Some(Authorization::Bearer(token))) => token,
+                Ok(None) => return self.challenge(None),
+                _ => return self.challenge(Some("invalid_request")),
+            };
+            match (self.verifier)(token).await {
+                Some(principal) => {
+                    ctx.insert(principal);
+                    next.run(ctx).await
+                }
+                None => self.challenge(Some("invalid_token")),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        request::{request::Request, request_from_reader},
+        router::router::{HandlerError, Router},
+    };
+
+    #[derive(Debug)]
+    struct Client(u32);
+
+    fn router() -> Router {
+        let mut router = Router::new();
+        router.add_middleware(BearerAuth::new("api", |token: String| async move {
+            (token == "t0ken").then_some(Client(7))
+        }));
+        router.get("/orders", |ctx: Context| async move {
+            let Client(id) = ctx.get::<Client>().ok_or(HandlerError::InternalError)?;
+            let mut response = Response::new();
+            response.body(id.to_string().into_bytes());
+            Ok(response)
+        });
+        router
+    }
+
+    async fn request(authorization: Option<&str>) -> Request {
+        let header =
+            authorization.map_or(String::new(), |value| format!("Authorization: {value}\r\n"));
+        let raw = format!("GET /orders HTTP/1.1\r\n{header}\r\n");
+        request_from_reader(raw.as_bytes()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn verifies_bearer_tokens() {
+        let router = router();
+        let allowed = router
+            .handle_request(request(Some("Bearer t0ken")).await, Response::new())
+            .await;
+        assert_eq!(allowed.status_code(), 200);
+        assert_eq!(allowed.body_bytes(), b"7");
+
+        let cases = [
+            (None, "Bearer realm=\"api\""),
+            (
+                Some("Bearer expired"),
+                "Bearer realm=\"api\", error=\"invalid_token\"",
+            ),
+            (
+                Some("Basic YWxpY2U6c2VjcmV0"),
+                "Bearer realm=\"api\", error=\"invalid_request\"",
+            ),
+        ];
+        for (authorization, challenge) in cases {
+            let denied = router
+                .handle_request(request(authorization).await, Response::new())
+                .await;
+            assert_eq!(denied.status_code(), 401);
+            assert_eq!(denied.headers().get("www-authenticate"), Some(challenge));
+        }
+    }
+}
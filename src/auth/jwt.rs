@@ -0,0 +1,505 @@
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{Mutex, RwLock},
+    time::{Duration, Instant, SystemTime},
+};
+
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm},
+};
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+use crate::{
+    auth::auth::{Scope, bearer_challenge},
+    headers::typed::Authorization,
+    response::Response,
+    router::{
+        middleware::{BoxFuture, Middleware, Next},
+        router::Context,
+    },
+};
+
+const ALGORITHMS: [Algorithm; 3] = [Algorithm::HS256, Algorithm::RS256, Algorithm::ES256];
+const FORCED_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone)]
+pub struct VerificationKey {
+    pub id: Option<String>,
+    pub algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+impl VerificationKey {
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self {
+            id: None,
+            algorithm: Algorithm::HS256,
+            key: DecodingKey::from_secret(secret),
+        }
+    }
+
+    pub fn rs256_pem(pem: &[u8]) -> Result<Self, JwtError> {
+        Ok(Self {
+            id: None,
+            algorithm: Algorithm::RS256,
+            key: DecodingKey::from_rsa_pem(pem).map_err(|_| JwtError::InvalidKey)?,
+        })
+    }
+
+    pub fn es256_pem(pem: &[u8]) -> Result<Self, JwtError> {
+        Ok(Self {
+            id: None,
+            algorithm: Algorithm::ES256,
+            key: DecodingKey::from_ec_pem(pem).map_err(|_| JwtError::InvalidKey)?,
+        })
+    }
+
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, JwtError> {
+        let algorithm = match (&jwk.algorithm, jwk.common.key_algorithm) {
+            (AlgorithmParameters::OctetKey(_), None | Some(KeyAlgorithm::HS256)) => {
+                Algorithm::HS256
+            }
+            (AlgorithmParameters::RSA(_), None | Some(KeyAlgorithm::RS256)) => Algorithm::RS256,
+            (AlgorithmParameters::EllipticCurve(params), None | Some(KeyAlgorithm::ES256))
+                if params.curve == EllipticCurve::P256 =>
+            {
+                Algorithm::ES256
+            }
+            _ => return Err(JwtError::UnsupportedKey),
+        };
+        Ok(Self {
+            id: jwk.common.key_id.clone(),
+            algorithm,
+            key: DecodingKey::from_jwk(jwk).map_err(|_| JwtError::InvalidKey)?,
+        })
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+}
+
+struct JwksFile {
+    path: PathBuf,
+    refresh_interval: Duration,
+    checked: Mutex<(Instant, Option<SystemTime>)>,
+}
+
+pub struct JwtAuth<C> {
+    keys: RwLock<Vec<VerificationKey>>,
+    jwks: Option<JwksFile>,
+    validation: Validation,
+    realm: String,
+    scope: Scope,
+    claims: PhantomData<fn() -> C>,
+}
+
+impl<C: DeserializeOwned + Send + Sync + 'static> JwtAuth<C> {
+    pub fn new(keys: Vec<VerificationKey>) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_nbf = true;
+        validation.validate_aud = false;
+        validation.leeway = 0;
+        Self {
+            keys: RwLock::new(keys),
+            jwks: None,
+            validation,
+            realm: "api".to_string(),
+            scope: Scope::default(),
+            claims: PhantomData,
+        }
+    }
+
+    pub fn from_jwks_file(path: impl AsRef<Path>) -> Result<Self, JwtError> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified(&path);
+        let content = std::fs::read(&path).map_err(|e| JwtError::Io(e.to_string()))?;
+        let mut auth = JwtAuth::new(parse_jwks(&content)?);
+        auth.jwks = Some(JwksFile {
+            path,
+            refresh_interval: Duration::from_secs(60),
+            checked: Mutex::new((Instant::now(), modified)),
+        });
+        Ok(auth)
+    }
+
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        self.validation.set_issuer(&[issuer]);
+        self
+    }
+
+    pub fn audience(mut self, audience: &str) -> Self {
+        self.validation.set_audience(&[audience]);
+        self.validation.validate_aud = true;
+        self
+    }
+
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        self.validation.leeway = leeway.as_secs();
+        self
+    }
+
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        if let Some(jwks) = &mut self.jwks {
+            jwks.refresh_interval = interval;
+        }
+        self
+    }
+
+    pub fn realm(mut self, realm: &str) -> Self {
+        self.realm = realm.to_string();
+        self
+    }
+
+    pub fn protect(mut self, prefix: &str) -> Self {
+        self.scope.add(prefix);
+        self
+    }
+
+    pub fn verify(&self, token: &str) -> Result<C, JwtError> {
+        let header = jsonwebtoken::decode_header(token).map_err(|_| JwtError::InvalidToken)?;
+        if !ALGORITHMS.contains(&header.alg) {
+            return Err(JwtError::InvalidToken);
+        }
+
+        let key = {
+            let keys = self
+                .keys
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let candidates = || keys.iter().filter(|key| key.algorithm == header.alg);
+            match &header.kid {
+                Some(kid) => candidates()
+                    .find(|key| key.id.as_ref() == Some(kid))
+                    .or_else(|| candidates().find(|key| key.id.is_none())),
+                None => candidates().next(),
+            }
+            .map(|key| key.key.clone())
+        };
+        let Some(key) = key else {
+            return Err(JwtError::UnknownKey);
+        };
+
+        let mut validation = self.validation.clone();
+        validation.algorithms = vec![header.alg];
+        jsonwebtoken::decode::<C>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|_| JwtError::InvalidToken)
+    }
+
+    async fn refresh(&self, force: bool) {
+        let Some(jwks) = &self.jwks else {
+            return;
+        };
+        let known = {
+            let mut checked = jwks
+                .checked
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let interval = if force {
+                FORCED_REFRESH_INTERVAL.min(jwks.refresh_interval)
+            } else {
+                jwks.refresh_interval
+            };
+            if checked.0.elapsed() < interval {
+                return;
+            }
+            checked.0 = Instant::now();
+            checked.1
+        };
+
+        let current = tokio::fs::metadata(&jwks.path)
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if current == known {
+            return;
+        }
+
+        let keys = match tokio::fs::read(&jwks.path).await {
+            Ok(content) => parse_jwks(&content),
+            Err(e) => Err(JwtError::Io(e.to_string())),
+        };
+        match keys {
+            Ok(keys) => {
+                *self
+                    .keys
+                    .write()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()) = keys;
+                jwks.checked
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .1 = current;
+            }
+            Err(e) => warn!("Cannot reload JWKS from {}: {}", jwks.path.display(), e),
+        }
+    }
+}
+
+impl<C: DeserializeOwned + Send + Sync + 'static> Middleware for JwtAuth<C> {
+    fn handle<'a>(&'a self, mut ctx: Context, next: Next<'a>) -> BoxFuture<'a, Response> {
+        Box::pin(async move {
+            if !self.scope.covers(&ctx.request.request_line.request_target) {
+                return next.run(ctx).await;
+            }
+            let token = match ctx.request.headers.typed_get::<Authorization>() {
+                Ok(Some(Authorization::Bearer(token))) => token,
+                Ok(None) => return bearer_challenge(&self.realm, None),
+                _ => return bearer_challenge(&self.realm, Some("invalid_request")),
+            };
+
+            self.refresh(false).await;
+            let mut claims = self.verify(&token);
+            if let Err(JwtError::UnknownKey) = claims {
+                self.refresh(true).await;
+                claims = self.verify(&token);
+            }
+
+            match claims {
+                Ok(claims) => {
+                    ctx.insert(claims);
+                    next.run(ctx).await
+                }
+                Err(_) => bearer_challenge(&self.realm, Some("invalid_token")),
+            }
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JwtError {
+    Io(String),
+    InvalidJwks,
+    InvalidKey,
+    UnsupportedKey,
+    UnknownKey,
+    InvalidToken,
+}
+
+impl std::fmt::Display for JwtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Cannot read key file: {}", e),
+            Self::InvalidJwks => write!(f, "Invalid JWKS document"),
+            Self::InvalidKey => write!(f, "Invalid verification key"),
+            Self::UnsupportedKey => write!(f, "Only HS256, RS256 and ES256 keys are supported"),
+            Self::UnknownKey => write!(f, "No key matches the token"),
+            Self::InvalidToken => write!(f, "Invalid token"),
+        }
+    }
+}
+
+impl std::error::Error for JwtError {}
+
+fn parse_jwks(content: &[u8]) -> Result<Vec<VerificationKey>, JwtError> {
+    let set: JwkSet = serde_json::from_slice(content).map_err(|_| JwtError::InvalidJwks)?;
+    let mut keys = Vec::new();
+    for jwk in set.keys.iter() {
+        match VerificationKey::from_jwk(jwk) {
+            Ok(key) => keys.push(key),
+            Err(e) => warn!("Skipping JWK {:?}: {}", jwk.common.key_id, e),
+        }
+    }
+    Ok(keys)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        request::{request::Request, request_from_reader},
+        router::router::{HandlerError, Router},
+    };
+    use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use ring::{
+        rand::SystemRandom,
+        signature::{ECDSA_P256_SHA256_FIXED_SIGNING, EcdsaKeyPair, KeyPair},
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+        exp: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        nbf: Option<i64>,
+        iss: String,
+        aud: String,
+    }
+
+    fn claims(exp_offset: i64) -> Claims {
+        Claims {
+            sub: "alice".to_string(),
+            exp: chrono::Utc::now().timestamp() + exp_offset,
+            nbf: None,
+            iss: "https://id.example.com".to_string(),
+            aud: "orders".to_string(),
+        }
+    }
+
+    fn router(auth: JwtAuth<Claims>) -> Router {
+        let mut router = Router::new();
+        router.add_middleware(auth);
+        router.get("/orders", |ctx: Context| async move {
+            let claims = ctx.get::<Claims>().ok_or(HandlerError::InternalError)?;
+            let mut response = Response::new();
+            response.body(claims.sub.clone().into_bytes());
+            Ok(response)
+        });
+        router
+    }
+
+    async fn request(token: &str) -> Request {
+        let raw = format!("GET /orders HTTP/1.1\r\nAuthorization: Bearer {token}\r\n\r\n");
+        request_from_reader(raw.as_bytes()).await.unwrap()
+    }
+
+    async fn status(router: &Router, token: &str) -> u16 {
+        router
+            .handle_request(request(token).await, Response::new())
+            .await
+            .status_code()
+    }
+
+    fn hs256(claims: &Claims) -> String {
+        encode(
+            &Header::default(),
+            claims,
+            &EncodingKey::from_secret(b"shared secret"),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn validates_hs256_claims() {
+        let router = router(
+            JwtAuth::new(vec![VerificationKey::hs256(b"shared secret")])
+                .issuer("https://id.example.com")
+                .audience("orders")
+                .leeway(Duration::from_secs(30)),
+        );
+
+        let response = router
+            .handle_request(request(&hs256(&claims(60))).await, Response::new())
+            .await;
+        assert_eq!(response.status_code(), 200);
+        assert_eq!(response.body_bytes(), b"alice");
+
+        assert_eq!(status(&router, &hs256(&claims(-10))).await, 200);
+        assert_eq!(status(&router, &hs256(&claims(-120))).await, 401);
+
+        let mut early = claims(600);
+        early.nbf = Some(chrono::Utc::now().timestamp() + 300);
+        assert_eq!(status(&router, &hs256(&early)).await, 401);
+
+        let mut foreign = claims(60);
+        foreign.iss = "https://evil.example.com".to_string();
+        assert_eq!(status(&router, &hs256(&foreign)).await, 401);
+
+        let mut other_audience = claims(60);
+        other_audience.aud = "billing".to_string();
+        assert_eq!(status(&router, &hs256(&other_audience)).await, 401);
+
+        let forged = encode(
+            &Header::default(),
+            &claims(60),
+            &EncodingKey::from_secret(b"guessed"),
+        )
+        .unwrap();
+        let response = router
+            .handle_request(request(&forged).await, Response::new())
+            .await;
+        assert_eq!(response.status_code(), 401);
+        assert_eq!(
+            response.headers().get("www-authenticate"),
+            Some("Bearer realm=\"api\", error=\"invalid_token\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn keys_without_id_match_any_kid() {
+        let router = router(JwtAuth::new(vec![
+            VerificationKey::hs256(b"other secret").with_id("other"),
+            VerificationKey::hs256(b"shared secret"),
+        ]));
+        let token = |kid: &str| {
+            let header = Header {
+                kid: Some(kid.to_string()),
+                ..Header::default()
+            };
+            encode(
+                &header,
+                &claims(60),
+                &EncodingKey::from_secret(b"shared secret"),
+            )
+            .unwrap()
+        };
+        assert_eq!(status(&router, &token("rotated")).await, 200);
+        assert_eq!(status(&router, &token("other")).await, 401);
+    }
+
+    struct EcKey {
+        pkcs8: Vec<u8>,
+        jwk: String,
+    }
+
+    fn ec_key(kid: &str) -> EcKey {
+        let random = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &random)
+            .unwrap()
+            .as_ref()
+            .to_vec();
+        let pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &pkcs8, &random).unwrap();
+        let point = pair.public_key().as_ref();
+        let jwk = format!(
+            r#"{{"kty":"EC","crv":"P-256","kid":"{kid}","alg":"ES256","x":"{}","y":"{}"}}"#,
+            URL_SAFE_NO_PAD.encode(&point[1..33]),
+            URL_SAFE_NO_PAD.encode(&point[33..])
+        );
+        EcKey { pkcs8, jwk }
+    }
+
+    fn es256(key: &EcKey, kid: &str) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(kid.to_string());
+        encode(&header, &claims(60), &EncodingKey::from_ec_der(&key.pkcs8)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reloads_rotated_jwks_file() {
+        let path = std::env::temp_dir().join(format!("jwks-{}.json", std::process::id()));
+        let first = ec_key("first");
+        let second = ec_key("second");
+        std::fs::write(&path, format!(r#"{{"keys":[{}]}}"#, first.jwk)).unwrap();
+
+        let router = router(
+            JwtAuth::from_jwks_file(&path)
+                .unwrap()
+                .refresh_interval(Duration::from_secs(3600)),
+        );
+        assert_eq!(status(&router, &es256(&first, "first")).await, 200);
+        assert_eq!(status(&router, &es256(&second, "second")).await, 401);
+
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&path, format!(r#"{{"keys":[{}]}}"#, second.jwk)).unwrap();
+        assert_eq!(status(&router, &es256(&second, "second")).await, 401);
+        std::thread::sleep(FORCED_REFRESH_INTERVAL);
+        assert_eq!(status(&router, &es256(&second, "second")).await, 200);
+        assert_eq!(status(&router, &es256(&first, "first")).await, 401);
+        assert_eq!(status(&router, &es256(&first, "second")).await, 401);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
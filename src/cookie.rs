@@ -0,0 +1,6 @@
+pub mod cookie;
+pub mod jar;
+pub mod key;
+pub use cookie::{Cookie, CookieError, SameSite};
+pub use jar::CookieJar;
+pub use key::{CookieKeys, Key};
@@ -0,0 +1,247 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::headers::typed::{Date, Header};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl std::fmt::Display for SameSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Strict => write!(f, "Strict"),
+            Self::Lax => write!(f, "Lax"),
+            Self::None => write!(f, "None"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub path: Option<String>,
+    pub domain: Option<String>,
+    pub max_age: Option<Duration>,
+    pub expires: Option<DateTime<Utc>>,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: Option<SameSite>,
+    pub partitioned: bool,
+}
+
+impl Cookie {
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            path: None,
+            domain: None,
+            max_age: None,
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+            partitioned: false,
+        }
+    }
+
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_string());
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn expires(mut self, expires: DateTime<Utc>) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    pub fn partitioned(mut self, partitioned: bool) -> Self {
+        self.partitioned = partitioned;
+        self
+    }
+
+    pub fn removal(mut self) -> Self {
+        self.value.clear();
+        self.max_age = Some(Duration::ZERO);
+        self.expires = Some(DateTime::UNIX_EPOCH);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), CookieError> {
+        if !is_token(&self.name) {
+            return Err(CookieError::InvalidName);
+        }
+        let value = self
+            .value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(&self.value);
+        if !value.bytes().all(is_cookie_octet) {
+            return Err(CookieError::InvalidValue);
+        }
+        let attributes = [self.path.as_deref(), self.domain.as_deref()];
+        if attributes
+            .iter()
+            .flatten()
+            .any(|attribute| attribute.chars().any(|c| c.is_ascii_control() || c == ';'))
+        {
+            return Err(CookieError::InvalidAttribute);
+        }
+        if (self.partitioned || self.same_site == Some(SameSite::None)) && !self.secure {
+            return Err(CookieError::RequiresSecure);
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Cookie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+        if let Some(path) = &self.path {
+            write!(f, "; Path={}", path)?;
+        }
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={}", domain)?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if let Some(expires) = self.expires {
+            write!(f, "; Expires={}", Date(expires).encode())?;
+        }
+        if self.secure {
+            write!(f, "; Secure")?;
+        }
+        if self.http_only {
+            write!(f, "; HttpOnly")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={}", same_site)?;
+        }
+        if self.partitioned {
+            write!(f, "; Partitioned")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CookieError {
+    InvalidName,
+    InvalidValue,
+    InvalidAttribute,
+    RequiresSecure,
+    InvalidKey,
+    MissingKey,
+}
+
+impl std::fmt::Display for CookieError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::InvalidName => write!(f, "Invalid cookie name, should be a token"),
+            Self::InvalidValue => write!(f, "Invalid cookie value"),
+            Self::InvalidAttribute => write!(f, "Invalid cookie attribute"),
+            Self::RequiresSecure => {
+                write!(f, "SameSite=None and Partitioned cookies must be Secure")
+            }
+            Self::InvalidKey => write!(f, "Cookie secret must be at least 32 bytes"),
+            Self::MissingKey => write!(f, "No cookie keys configured on the router"),
+        }
+    }
+}
+
+impl std::error::Error for CookieError {}
+
+fn is_token(value: &str) -> bool {
+    crate::headers::parser::is_token(value.as_bytes())
+}
+
+fn is_cookie_octet(b: u8) -> bool {
+    matches!(b, 0x21 | 0x23..=0x2b | 0x2d..=0x3a | 0x3c..=0x5b | 0x5d..=0x7e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn formats_every_attribute() {
+        let cookie = Cookie::new("id", "a3fWa")
+            .path("/docs")
+            .domain("example.com")
+            .max_age(Duration::from_secs(3600))
+            .expires(Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap())
+            .secure(true)
+            .http_only(true)
+            .same_site(SameSite::None)
+            .partitioned(true);
+
+        assert!(cookie.validate().is_ok());
+        assert_eq!(
+            cookie.to_string(),
+            "id=a3fWa; Path=/docs; Domain=example.com; Max-Age=3600; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Secure; HttpOnly; SameSite=None; Partitioned"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_cookies() {
+        assert_eq!(
+            Cookie::new("bad name", "v").validate(),
+            Err(CookieError::InvalidName)
+        );
+        assert_eq!(
+            Cookie::new("id", "a;b").validate(),
+            Err(CookieError::InvalidValue)
+        );
+        assert_eq!(
+            Cookie::new("id", "v").path("/; Domain=evil.com").validate(),
+            Err(CookieError::InvalidAttribute)
+        );
+        assert_eq!(
+            Cookie::new("id", "v").partitioned(true).validate(),
+            Err(CookieError::RequiresSecure)
+        );
+        assert!(Cookie::new("id", "\"quoted\"").validate().is_ok());
+    }
+
+    #[test]
+    fn removal_expires_cookie() {
+        let cookie = Cookie::new("id", "a3fWa").path("/").removal();
+        assert_eq!(
+            cookie.to_string(),
+            "id=; Path=/; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use crate::headers::Headers;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CookieJar {
+    cookies: Vec<(String, String)>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(header: &str) -> Self {
+        let mut jar = CookieJar::new();
+        jar.extend(header);
+        jar
+    }
+
+    pub fn from_headers(headers: &Headers) -> Self {
+        let mut jar = CookieJar::new();
+        for header in headers.get_all("Cookie") {
+            jar.extend(header);
+        }
+        jar
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.cookies
+            .iter()
+            .find(|(cookie, _)| cookie == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.cookies
+            .iter()
+            .filter(move |(cookie, _)| cookie == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.cookies
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+
+    fn extend(&mut self, header: &str) {
+        for pair in header.split(';') {
+            let Some((name, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            self.cookies.push((name.to_string(), value.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cookie_header() {
+        let jar = CookieJar::parse("id=a3fWa; theme=\"dark\";  empty=; broken; lang=en");
+        assert_eq!(jar.get("id"), Some("a3fWa"));
+        assert_eq!(jar.get("theme"), Some("dark"));
+        assert_eq!(jar.get("empty"), Some(""));
+        assert_eq!(jar.get("lang"), Some("en"));
+        assert!(!jar.contains("broken"));
+        assert_eq!(jar.len(), 4);
+    }
+
+    #[test]
+    fn merges_split_cookie_headers() {
+        let mut headers = Headers::new();
+        headers.append("Cookie", "a=1; b=2").unwrap();
+        headers.append("cookie", "c=3; a=4").unwrap();
+
+        let jar = CookieJar::from_headers(&headers);
+        assert_eq!(jar.get("a"), Some("1"));
+        assert_eq!(jar.get_all("a").collect::<Vec<_>>(), ["1", "4"]);
+        assert_eq!(jar.get("c"), Some("3"));
+    }
+}
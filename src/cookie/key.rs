@@ -0,0 +1,228 @@
+use aes_gcm::{
+    Aes256Gcm, KeyInit, Nonce,
+    aead::{Aead, Generate, Payload},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::cookie::cookie::CookieError;
+
+const MIN_SECRET_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+
+#[derive(Clone)]
+pub struct Key {
+    signing: [u8; 32],
+    encryption: [u8; 32],
+}
+
+impl Key {
+    pub fn derive(secret: &[u8]) -> Result<Self, CookieError> {
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(CookieError::InvalidKey);
+        }
+        Ok(Self {
+            signing: hmac(secret, b"cookie-signing"),
+            encryption: hmac(secret, b"cookie-encryption"),
+        })
+    }
+
+    fn sign(&self, name: &str, value: &str) -> [u8; 32] {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.signing).expect("any key length");
+        mac.update(name.as_bytes());
+        mac.update(b"=");
+        mac.update(value.as_bytes());
+        mac.finalize().into_bytes().into()
+    }
+
+    fn verify(&self, name: &str, value: &str, tag: &[u8]) -> bool {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.signing).expect("any key length");
+        mac.update(name.as_bytes());
+        mac.update(b"=");
+        mac.update(value.as_bytes());
+        mac.verify_slice(tag).is_ok()
+    }
+
+    fn encrypt(&self, name: &str, value: &str) -> Result<Vec<u8>, CookieError> {
+        let cipher = Aes256Gcm::new(&self.encryption.into());
+        let nonce = Nonce::generate();
+        let payload = Payload {
+            msg: value.as_bytes(),
+            aad: name.as_bytes(),
+        };
+        let ciphertext = cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| CookieError::InvalidValue)?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    fn decrypt(&self, name: &str, sealed: &[u8]) -> Option<String> {
+        if sealed.len() < NONCE_LENGTH {
+            return None;
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+        let cipher = Aes256Gcm::new(&self.encryption.into());
+        let payload = Payload {
+            msg: ciphertext,
+            aad: name.as_bytes(),
+        };
+        let plaintext = cipher
+            .decrypt(&Nonce::try_from(nonce).ok()?, payload)
+            .ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+}
+
+impl std::fmt::Debug for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CookieKeys {
+    current: Key,
+    previous: Vec<Key>,
+}
+
+impl CookieKeys {
+    pub fn new(current: Key) -> Self {
+        Self {
+            current,
+            previous: Vec::new(),
+        }
+    }
+
+    pub fn with_previous(mut self, key: Key) -> Self {
+        self.previous.push(key);
+        self
+    }
+
+    pub fn sign(&self, name: &str, value: &str) -> String {
+        let tag = self.current.sign(name, value);
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(value),
+            URL_SAFE_NO_PAD.encode(tag)
+        )
+    }
+
+    pub fn verify(&self, name: &str, signed: &str) -> Option<String> {
+        let (value, tag) = signed.split_once('.')?;
+        let value = String::from_utf8(URL_SAFE_NO_PAD.decode(value).ok()?).ok()?;
+        let tag = URL_SAFE_NO_PAD.decode(tag).ok()?;
+        self.keys()
+            .any(|key| key.verify(name, &value, &tag))
+            .then_some(value)
+    }
+
+    pub fn encrypt(&self, name: &str, value: &str) -> Result<String, CookieError> {
+        let sealed = self.current.encrypt(name, value)?;
+        Ok(URL_SAFE_NO_PAD.encode(sealed))
+    }
+
+    pub fn decrypt(&self, name: &str, encrypted: &str) -> Option<String> {
+        let sealed = URL_SAFE_NO_PAD.decode(encrypted).ok()?;
+        self.keys().find_map(|key| key.decrypt(name, &sealed))
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &Key> {
+        std::iter::once(&self.current).chain(self.previous.iter())
+    }
+}
+
+fn hmac(secret: &[u8], label: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("any key length");
+    mac.update(label);
+    mac.finalize().into_bytes().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> Key {
+        Key::derive(&[seed; 32]).unwrap()
+    }
+
+    #[test]
+    fn rejects_short_secret() {
+        assert!(matches!(
+            Key::derive(b"too short"),
+            Err(CookieError::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn signed_value_detects_tampering() {
+        let keys = CookieKeys::new(key(1));
+        let signed = keys.sign("user", "alice");
+        assert!(signed.starts_with("YWxpY2U."));
+        assert_eq!(keys.verify("user", &signed).as_deref(), Some("alice"));
+
+        let forged = signed.replacen("YWxpY2U", &URL_SAFE_NO_PAD.encode("admin"), 1);
+        assert_eq!(keys.verify("user", &forged), None);
+        assert_eq!(keys.verify("role", &signed), None);
+        assert_eq!(keys.verify("user", "alice"), None);
+        assert_eq!(keys.verify("user", "YWxpY2U"), None);
+    }
+
+    #[test]
+    fn signed_value_may_hold_any_text() {
+        let keys = CookieKeys::new(key(1));
+        let value = "dark mode, \"wide\"; lang=fr";
+        let signed = keys.sign("prefs", value);
+        assert!(
+            crate::cookie::Cookie::new("prefs", &signed)
+                .validate()
+                .is_ok()
+        );
+        assert_eq!(keys.verify("prefs", &signed).as_deref(), Some(value));
+    }
+
+    #[test]
+    fn encrypted_value_round_trips() {
+        let keys = CookieKeys::new(key(1));
+        let encrypted = keys.encrypt("session", "secret data; with=chars").unwrap();
+        assert!(!encrypted.contains("secret"));
+        assert_ne!(
+            keys.encrypt("session", "secret data; with=chars").unwrap(),
+            encrypted
+        );
+        assert_eq!(
+            keys.decrypt("session", &encrypted).as_deref(),
+            Some("secret data; with=chars")
+        );
+        assert_eq!(keys.decrypt("other", &encrypted), None);
+
+        let mut tampered = URL_SAFE_NO_PAD.decode(&encrypted).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            keys.decrypt("session", &URL_SAFE_NO_PAD.encode(tampered)),
+            None
+        );
+    }
+
+    #[test]
+    fn previous_keys_still_verify() {
+        let old = CookieKeys::new(key(1));
+        let signed = old.sign("user", "alice");
+        let encrypted = old.encrypt("session", "42").unwrap();
+
+        let rotated = CookieKeys::new(key(2)).with_previous(key(1));
+        assert_eq!(rotated.verify("user", &signed).as_deref(), Some("alice"));
+        assert_eq!(
+            rotated.decrypt("session", &encrypted).as_deref(),
+            Some("42")
+        );
+        assert_ne!(rotated.sign("user", "alice"), signed);
+
+        let retired = CookieKeys::new(key(2));
+        assert_eq!(retired.verify("user", &signed), None);
+        assert_eq!(retired.decrypt("session", &encrypted), None);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod cors;
+pub use cors::Cors;
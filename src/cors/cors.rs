@@ -0,0 +1,417 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    response::{Response, Status},
+    router::{
+        middleware::{BoxFuture, Middleware, Next},
+        router::Context,
+    },
+};
+
+type OriginPredicate = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+enum Origins {
+    Any,
+    List(Vec<String>),
+    Predicate(OriginPredicate),
+}
+
+#[derive(Clone)]
+pub struct Cors {
+    origins: Origins,
+    methods: Vec<String>,
+    headers: Option<Vec<String>>,
+    expose_headers: Vec<String>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl Cors {
+    pub fn new() -> Self {
+        Self {
+            origins: Origins::List(Vec::new()),
+            methods: ["GET", "HEAD", "POST"].map(str::to_string).to_vec(),
+            headers: Some(Vec::new()),
+            expose_headers: Vec::new(),
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    pub fn allow_any_origin(mut self) -> Self {
+        self.origins = Origins::Any;
+        self.check_credentials();
+        self
+    }
+
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        let origin = origin.trim_end_matches('/').to_string();
+        match &mut self.origins {
+            Origins::List(origins) => origins.push(origin),
+            _ => self.origins = Origins::List(vec![origin]),
+        }
+        self
+    }
+
+    pub fn allow_origin_fn(
+        mut self,
+        predicate: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.origins = Origins::Predicate(Arc::new(predicate));
+        self
+    }
+
+    pub fn allow_methods(mut self, methods: &[&str]) -> Self {
+        self.methods = methods
+            .iter()
+            .map(|method| method.to_ascii_uppercase())
+            .collect();
+        self
+    }
+
+    pub fn allow_headers(mut self, headers: &[&str]) -> Self {
+        self.headers = Some(headers.iter().map(|header| header.to_string()).collect());
+        self
+    }
+
+    pub fn allow_any_header(mut self) -> Self {
+        self.headers = None;
+        self
+    }
+
+    pub fn expose_headers(mut self, headers: &[&str]) -> Self {
+        self.expose_headers = headers.iter().map(|header| header.to_string()).collect();
+        self
+    }
+
+    pub fn allow_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self.check_credentials();
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    fn check_credentials(&self) {
+        if self.credentials && matches!(self.origins, Origins::Any) {
+            panic!(
+                "Cors cannot allow credentials with any origin, \
+                use allow_origin or allow_origin_fn instead"
+            );
+        }
+    }
+
+    fn is_allowed(&self, origin: &str) -> bool {
+        match &self.origins {
+            Origins::Any => true,
+            Origins::List(origins) => origins.iter().any(|allowed| allowed == origin),
+            Origins::Predicate(predicate) => predicate(origin),
+        }
+    }
+
+    fn allow_origin_header(&self, response: &mut Response, origin: &str) {
+        let value = match self.origins {
+            Origins::Any => "*",
+            _ => origin,
+        };
+        response
+            .set_header("Access-Control-Allow-Origin", value)
+            .unwrap();
+        if self.credentials {
+            response
+                .set_header("Access-Control-Allow-Credentials", "true")
+                .unwrap();
+        }
+    }
+
+    fn preflight(&self, ctx: &Context, origin: &str, method: &str) -> Response {
+        let mut response = Response::new();
+        response.append_header("Vary", "Origin").unwrap();
+        response
+            .append_header(
+                "Vary",
+                "Access-Control-Request-Method, Access-Control-Request-Headers",
+            )
+            .unwrap();
+
+        let requested_headers: Vec<&str> = ctx
+            .request
+            .headers
+            .get_all("Access-Control-Request-Headers")
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|header| !header.is_empty())
+            .collect();
+        let headers_allowed = match &self.headers {
+            None => true,
+            Some(allowed) => requested_headers.iter().all(|header| {
+                allowed
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(header))
+            }),
+        };
+        let method_allowed = self
+            .methods
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(method));
+
+        if !self.is_allowed(origin) || !method_allowed || !headers_allowed {
+            response.status(Status::FORBIDDEN);
+            return response;
+        }
+
+        response.status(Status::NO_CONTENT);
+        self.allow_origin_header(&mut response, origin);
+        response
+            .set_header("Access-Control-Allow-Methods", &self.methods.join(", "))
+            .unwrap();
+        let allowed_headers = match &self.headers {
+            None => requested_headers.join(", "),
+            Some(allowed) => allowed.join(", "),
+        };
+        if !allowed_headers.is_empty() {
+            response
+                .set_header("Access-Control-Allow-Headers", &allowed_headers)
+                .unwrap();
+        }
+        if let Some(max_age) = self.max_age {
+            response
+                .set_header("Access-Control-Max-Age", &max_age.as_secs().to_string())
+                .unwrap();
+        }
+        response
+    }
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for Cors {
+    fn handle<'a>(&'a self, ctx: Context, next: Next<'a>) -> BoxFuture<'a, Response> {
+        Box::pin(async move {
+            let Some(origin) = ctx.request.headers.get("Origin").map(str::to_string) else {
+                let mut response = next.run(ctx).await;
+                vary_origin(&mut response);
+                return response;
+            };
+
+            if ctx.request.request_line.method == "OPTIONS"
+                && let Some(method) = ctx.request.headers.get("Access-Control-Request-Method")
+            {
+                return self.preflight(&ctx, &origin, method);
+            }
+
+            let mut response = next.run(ctx).await;
+            vary_origin(&mut response);
+            if self.is_allowed(&origin) {
+                self.allow_origin_header(&mut response, &origin);
+                if !self.expose_headers.is_empty() {
+                    response
+                        .set_header(
+                            "Access-Control-Expose-Headers",
+                            &self.expose_headers.join(", "),
+                        )
+                        .unwrap();
+                }
+            }
+            response
+        })
+    }
+}
+
+fn vary_origin(response: &mut Response) {
+    let listed = response
+        .headers()
+        .get_all("Vary")
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim() == "*" || value.trim().eq_ignore_ascii_case("Origin"));
+    if !listed {
+        response.append_header("Vary", "Origin").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        request::{request::Request, request_from_reader},
+        router::router::Router,
+    };
+
+    fn router(cors: Cors) -> Router {
+        let mut router = Router::new();
+        router.add_middleware(cors);
+        router.get("/coffee", |ctx: Context| async move {
+            let mut response = ctx.response;
+            response.body(b"black".to_vec());
+            Ok(response)
+        });
+        router.get("/tea", |ctx: Context| async move {
+            let mut response = ctx.response;
+            response.append_header("Vary", "Accept-Encoding, origin")?;
+            Ok(response)
+        });
+        router
+    }
+
+    async fn request(method: &str, headers: &str) -> Request {
+        let raw = format!("{method} /coffee HTTP/1.1\r\n{headers}\r\n");
+        request_from_reader(raw.as_bytes()).await.unwrap()
+    }
+
+    fn cors() -> Cors {
+        Cors::new()
+            .allow_origin("https://app.example.com")
+            .allow_methods(&["GET", "PUT"])
+            .allow_headers(&["Content-Type", "X-Request-Id"])
+            .allow_credentials(true)
+            .max_age(Duration::from_secs(600))
+    }
+
+    #[tokio::test]
+    async fn answers_preflight_without_handler() {
+        let router = router(cors());
+        let response = router
+            .handle_request(
+                request(
+                    "OPTIONS",
+                    "Origin: https://app.example.com\r\nAccess-Control-Request-Method: PUT\r\nAccess-Control-Request-Headers: content-type\r\n",
+                )
+                .await,
+                Response::new(),
+            )
+            .await;
+
+        let headers = response.headers();
+        assert_eq!(response.status_code(), 204);
+        assert_eq!(
+            headers.get("access-control-allow-origin"),
+            Some("https://app.example.com")
+        );
+        assert_eq!(
+            headers.get("access-control-allow-methods"),
+            Some("GET, PUT")
+        );
+        assert_eq!(
+            headers.get("access-control-allow-headers"),
+            Some("Content-Type, X-Request-Id")
+        );
+        assert_eq!(
+            headers.get("access-control-allow-credentials"),
+            Some("true")
+        );
+        assert_eq!(headers.get("access-control-max-age"), Some("600"));
+        assert_eq!(headers.get("vary"), Some("Origin"));
+    }
+
+    #[tokio::test]
+    async fn rejects_disallowed_preflight() {
+        let router = router(cors());
+        for headers in [
+            "Origin: https://evil.example.com\r\nAccess-Control-Request-Method: GET\r\n",
+            "Origin: https://app.example.com\r\nAccess-Control-Request-Method: DELETE\r\n",
+            "Origin: https://app.example.com\r\nAccess-Control-Request-Method: GET\r\nAccess-Control-Request-Headers: X-Secret\r\n",
+        ] {
+            let response = router
+                .handle_request(request("OPTIONS", headers).await, Response::new())
+                .await;
+            assert_eq!(response.status_code(), 403);
+            assert!(!response.headers().contains("access-control-allow-origin"));
+        }
+    }
+
+    #[tokio::test]
+    async fn decorates_actual_requests() {
+        let router = router(cors().expose_headers(&["X-Request-Id"]));
+        let response = router
+            .handle_request(
+                request("GET", "Origin: https://app.example.com\r\n").await,
+                Response::new(),
+            )
+            .await;
+        assert_eq!(response.body_bytes(), b"black");
+        assert_eq!(
+            response.headers().get("access-control-allow-origin"),
+            Some("https://app.example.com")
+        );
+        assert_eq!(
+            response.headers().get("access-control-expose-headers"),
+            Some("X-Request-Id")
+        );
+
+        let response = router
+            .handle_request(
+                request("GET", "Origin: https://evil.example.com\r\n").await,
+                Response::new(),
+            )
+            .await;
+        assert_eq!(response.body_bytes(), b"black");
+        assert!(!response.headers().contains("access-control-allow-origin"));
+        assert_eq!(response.headers().get("vary"), Some("Origin"));
+    }
+
+    #[tokio::test]
+    async fn any_origin_and_predicate() {
+        let any = router(Cors::new().allow_any_origin());
+        let response = any
+            .handle_request(
+                request("GET", "Origin: https://a.example.com\r\n").await,
+                Response::new(),
+            )
+            .await;
+        assert_eq!(
+            response.headers().get("access-control-allow-origin"),
+            Some("*")
+        );
+
+        let predicate =
+            router(Cors::new().allow_origin_fn(|origin| origin.ends_with(".example.com")));
+        let response = predicate
+            .handle_request(
+                request("GET", "Origin: https://b.example.com\r\n").await,
+                Response::new(),
+            )
+            .await;
+        assert_eq!(
+            response.headers().get("access-control-allow-origin"),
+            Some("https://b.example.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn varies_on_origin_without_duplicates() {
+        let router = router(cors());
+        let response = router
+            .handle_request(request("GET", "").await, Response::new())
+            .await;
+        assert_eq!(response.body_bytes(), b"black");
+        assert_eq!(response.headers().get("vary"), Some("Origin"));
+
+        let raw = "GET /tea HTTP/1.1\r\nOrigin: https://app.example.com\r\n\r\n";
+        let tea = request_from_reader(raw.as_bytes()).await.unwrap();
+        let response = router.handle_request(tea, Response::new()).await;
+        assert_eq!(
+            response.headers().get_combined("vary").as_deref(),
+            Some("Accept-Encoding, origin")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "credentials with any origin")]
+    fn rejects_credentials_with_any_origin() {
+        let _ = Cors::new().allow_any_origin().allow_credentials(true);
+    }
+
+    #[test]
+    #[should_panic(expected = "credentials with any origin")]
+    fn rejects_any_origin_with_credentials() {
+        let _ = Cors::new().allow_credentials(true).allow_any_origin();
+    }
+}
@@ -0,0 +1,2 @@
+pub mod extensions;
+pub use extensions::Extensions;
@@ -0,0 +1,92 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+#[derive(Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.downcast().ok().map(|previous| *previous))
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok().map(|value| *value))
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn extend(&mut self, other: Extensions) {
+        self.map.extend(other.map);
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl std::fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.map.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct RequestId(u32);
+
+    #[test]
+    fn values_are_keyed_by_type() {
+        let mut extensions = Extensions::new();
+        assert_eq!(extensions.insert(RequestId(1)), None);
+        assert_eq!(extensions.insert("principal".to_string()), None);
+        assert_eq!(extensions.insert(RequestId(2)), Some(RequestId(1)));
+
+        extensions.get_mut::<RequestId>().unwrap().0 += 1;
+        assert_eq!(extensions.get::<RequestId>(), Some(&RequestId(3)));
+        assert_eq!(extensions.get::<String>().unwrap(), "principal");
+        assert_eq!(extensions.remove::<String>().unwrap(), "principal");
+        assert!(!extensions.contains::<String>());
+        assert_eq!(extensions.len(), 1);
+
+        let mut other = Extensions::new();
+        other.insert(std::sync::Mutex::new(0u8));
+        extensions.extend(other);
+        assert_eq!(extensions.len(), 2);
+        assert!(extensions.contains::<std::sync::Mutex<u8>>());
+    }
+}
@@ -1,2 +1,7 @@
 pub mod headers;
+pub mod name;
+pub mod parser;
+pub mod typed;
 pub use headers::Headers;
+pub use name::{AsHeaderName, HeaderName};
+pub use typed::{Header, InvalidHeader};
@@ -1,114 +1,153 @@
-use std::{
-    collections::{HashMap, hash_map::Entry},
-    io::{BufWriter, Write},
+use tokio::io::{AsyncRead, BufReader};
+
+use crate::headers::{
+    name::{AsHeaderName, HeaderKey, HeaderName},
+    parser::{FieldParser, read_from},
 };
-use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 
-const MAX_LINE_LENGTH: usize = 8 * 1024;
 const CRLF: &str = "\r\n";
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Headers {
-    pub headers: HashMap<String, String>,
+    entries: Vec<(HeaderName, String)>,
 }
 
 impl Headers {
     pub fn new() -> Self {
         Self {
-            headers: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
         }
     }
 
     pub async fn parse(
         reader: &mut BufReader<impl AsyncRead + Unpin>,
     ) -> Result<Headers, HeadersError> {
-        let mut headers = Headers {
-            headers: HashMap::new(),
-        };
-        let mut buffer = String::new();
-        loop {
-            buffer.clear();
-            match reader.read_line(&mut buffer).await {
-                Ok(0) => return Err(HeadersError::ReadError),
-                Ok(bytes_read) => {
-                    if buffer == CRLF {
-                        break;
-                    }
-                    if bytes_read == MAX_LINE_LENGTH && !buffer.ends_with(CRLF) {
-                        return Err(HeadersError::LineTooLong);
-                    }
-
-                    if !buffer.ends_with(CRLF) {
-                        return Err(HeadersError::MalformedEndOfLine);
-                    }
-
-                    buffer = buffer.replace(CRLF, "");
-                    let parts: Vec<&str> = buffer.splitn(2, ":").collect();
-
-                    if parts.len() != 2 {
-                        return Err(HeadersError::MalformedPart);
-                    }
-
-                    let field = parts[0];
-                    let value = parts[1];
-
-                    Headers::check_field(field)?;
-
-                    let field_normalized = field.trim().to_lowercase();
-                    let value_trimmed = value.trim();
-
-                    if let Entry::Occupied(mut entry) = headers
-                        .headers
-                        .entry(field_normalized.trim().to_lowercase())
-                    {
-                        let value_string = entry.get_mut();
-                        value_string.reserve(value_trimmed.len() + 2);
-                        value_string.push_str(", ");
-                        value_string.push_str(value_trimmed);
-                    } else {
-                        headers
-                            .headers
-                            .insert(field_normalized, value_trimmed.to_string());
-                    }
-                }
-                Err(_) => return Err(HeadersError::ReadError),
-            }
-        }
-        Ok(headers)
+        read_from(reader, &mut Vec::new(), &mut FieldParser::new()).await
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buffer = Vec::new();
 
-        for (key, value) in self.headers.iter() {
-            let header_line = format!("{}: {}\r\n", key, value);
-            buffer.extend_from_slice(header_line.as_bytes());
+        for (name, value) in self.entries.iter() {
+            buffer.extend_from_slice(name.as_str().as_bytes());
+            buffer.extend_from_slice(b": ");
+            buffer.extend_from_slice(value.as_bytes());
+            buffer.extend_from_slice(CRLF.as_bytes());
         }
-        buffer.extend_from_slice(format!("\r\n").as_bytes());
+        buffer.extend_from_slice(CRLF.as_bytes());
         buffer
     }
 
-    pub fn set(&mut self, key: &str, value: &str) -> Result<(), HeadersError> {
-        Headers::check_field(key)?;
-        if let Entry::Occupied(mut entry) = self.headers.entry(key.trim().to_lowercase()) {
-            let value_string = entry.get_mut();
-            value_string.reserve(value.len() + 2);
-            value_string.push_str(", ");
-            value_string.push_str(value);
-            Ok(())
+    pub fn append(&mut self, name: impl AsHeaderName, value: &str) -> Result<(), HeadersError> {
+        let name = Headers::header_name(&name)?;
+        Headers::check_value(value)?;
+        self.entries.push((name, value.trim().to_string()));
+        Ok(())
+    }
+
+    pub fn insert(&mut self, name: impl AsHeaderName, value: &str) -> Result<(), HeadersError> {
+        let name = Headers::header_name(&name)?;
+        Headers::check_value(value)?;
+        let value = value.trim();
+        let mut found = false;
+        self.entries.retain_mut(|(field, current)| {
+            if *field != name {
+                return true;
+            }
+            if found {
+                return false;
+            }
+            found = true;
+            *current = value.to_string();
+            true
+        });
+        if !found {
+            self.entries.push((name, value.to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: impl AsHeaderName) -> Option<&str> {
+        self.position(name.key())
+            .map(|index| self.entries[index].1.as_str())
+    }
+
+    pub fn get_all(&self, name: impl AsHeaderName) -> impl Iterator<Item = &str> {
+        let name = name.to_header_name();
+        self.entries
+            .iter()
+            .filter(move |(field, _)| name.key().matches(field))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_combined(&self, name: impl AsHeaderName) -> Option<String> {
+        let values: Vec<&str> = self.get_all(name).collect();
+        if values.is_empty() {
+            None
         } else {
-            self.headers
-                .insert(key.trim().to_lowercase(), value.trim().to_string());
-            Ok(())
+            Some(values.join(", "))
         }
     }
 
-    pub fn get(&self, key: &str) -> Option<&String> {
-        self.headers.get(key.to_lowercase().as_str())
+    pub fn contains(&self, name: impl AsHeaderName) -> bool {
+        self.position(name.key()).is_some()
+    }
+
+    pub fn remove(&mut self, name: impl AsHeaderName) -> Vec<String> {
+        self.remove_key(name.key())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn position(&self, key: HeaderKey<'_>) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(field, _)| key.matches(field))
+    }
+
+    fn remove_key(&mut self, key: HeaderKey<'_>) -> Vec<String> {
+        let mut removed = Vec::new();
+        self.entries.retain(|(field, value)| {
+            if key.matches(field) {
+                removed.push(value.clone());
+                false
+            } else {
+                true
+            }
+        });
+        removed
+    }
+
+    fn header_name(name: &impl AsHeaderName) -> Result<HeaderName, HeadersError> {
+        let raw = name.as_str();
+        Headers::check_field(raw)?;
+        if raw.len() == raw.trim().len() {
+            Ok(name.to_header_name())
+        } else {
+            Ok(HeaderName::new(raw.trim()))
+        }
     }
 
     fn check_field(field: &str) -> Result<(), HeadersError> {
-        if field.ends_with(" ") {
+        if field.ends_with(" ") || field.trim().is_empty() {
             return Err(HeadersError::MalformedFieldName);
         }
 
@@ -136,6 +175,19 @@ impl Headers {
         }
         Ok(())
     }
+
+    fn check_value(value: &str) -> Result<(), HeadersError> {
+        if value.chars().any(|c| c.is_ascii_control() && c != '\t') {
+            return Err(HeadersError::MalformedFieldValue);
+        }
+        Ok(())
+    }
+}
+
+impl Default for Headers {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -144,12 +196,33 @@ pub enum HeadersError {
     MalformedEndOfLine,
     MalformedPart,
     MalformedFieldName,
+    MalformedFieldValue,
     ReadError,
+    TooManyFields,
+    HeadTooLarge,
 }
 
+impl std::fmt::Display for HeadersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::LineTooLong => write!(f, "Line too long"),
+            Self::MalformedEndOfLine => write!(f, "Malformed end of line missing '\r\n'"),
+            Self::MalformedPart => write!(f, "Malformed header line, expected 'name: value'"),
+            Self::MalformedFieldName => write!(f, "Malformed field name"),
+            Self::MalformedFieldValue => write!(f, "Malformed field value"),
+            Self::ReadError => write!(f, "Error while reading bytes."),
+            Self::TooManyFields => write!(f, "Too many header fields"),
+            Self::HeadTooLarge => write!(f, "Request head exceeds the size limit"),
+        }
+    }
+}
+
+impl std::error::Error for HeadersError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[tokio::test]
     async fn valid_single_header() {
@@ -159,7 +232,7 @@ mod tests {
         .await
         {
             Ok(v) => {
-                assert_eq!(v.headers["host"], "localhost:42069");
+                assert_eq!(v.get("host").unwrap(), "localhost:42069");
             }
             Err(_) => panic!("error"),
         }
@@ -197,8 +270,8 @@ mod tests {
         .await
         {
             Ok(v) => {
-                assert_eq!(v.headers["host"], "localhost:42069");
-                assert_eq!(v.headers["user-agent"], "curl/8.5.0")
+                assert_eq!(v.get("host").unwrap(), "localhost:42069");
+                assert_eq!(v.get("user-agent").unwrap(), "curl/8.5.0")
             }
             Err(_) => panic!("error"),
         }
@@ -212,9 +285,182 @@ mod tests {
         .await
         {
             Ok(v) => {
-                assert_eq!(v.headers["host"], "localhost:42069, localhost:3333");
+                let values: Vec<&str> = v.get_all("host").collect();
+                assert_eq!(values, ["localhost:42069", "localhost:3333"]);
+                assert_eq!(
+                    v.get_combined("host").unwrap(),
+                    "localhost:42069, localhost:3333"
+                );
+            }
+            Err(_) => panic!("error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn preserves_order_and_casing() {
+        match Headers::parse(&mut BufReader::new(
+            "Host: localhost:42069\r\nX-Custom-ID: 42\r\nAccept: */*\r\n\r\n".as_bytes(),
+        ))
+        .await
+        {
+            Ok(v) => {
+                assert_eq!(v.get("x-custom-id").unwrap(), "42");
+                assert_eq!(
+                    v.to_bytes(),
+                    b"Host: localhost:42069\r\nX-Custom-ID: 42\r\nAccept: */*\r\n\r\n"
+                );
             }
             Err(_) => panic!("error"),
         }
     }
+
+    #[test]
+    fn set_cookie_values_stay_separate() {
+        let mut headers = Headers::new();
+        headers.append("Set-Cookie", "a=1; Path=/").unwrap();
+        headers
+            .append("Set-Cookie", "b=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap();
+        assert_eq!(
+            headers.to_bytes(),
+            b"Set-Cookie: a=1; Path=/\r\nSet-Cookie: b=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn insert_replaces_every_value() {
+        let mut headers = Headers::new();
+        headers.append("Vary", "Origin").unwrap();
+        headers.append("Server", "rust").unwrap();
+        headers.append("vary", "Accept-Encoding").unwrap();
+        headers.insert("VARY", "Cookie").unwrap();
+        assert_eq!(headers.get_all("vary").collect::<Vec<_>>(), ["Cookie"]);
+        assert_eq!(headers.to_bytes(), b"Vary: Cookie\r\nServer: rust\r\n\r\n");
+    }
+
+    #[test]
+    fn remove_returns_all_values() {
+        let mut headers = Headers::new();
+        headers.append("Accept", "text/html").unwrap();
+        headers.append("Host", "localhost").unwrap();
+        headers.append("accept", "application/json").unwrap();
+        assert_eq!(headers.remove("Accept"), ["text/html", "application/json"]);
+        assert!(!headers.contains("accept"));
+        assert_eq!(headers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn obs_fold_replaced_with_space() {
+        match Headers::parse(&mut BufReader::new(
+            "X-Folded: first\r\n \t second\r\nHost: localhost\r\n\r\n".as_bytes(),
+        ))
+        .await
+        {
+            Ok(v) => {
+                assert_eq!(v.get("x-folded").unwrap(), "first second");
+                assert_eq!(v.get("host").unwrap(), "localhost");
+            }
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn obs_fold_without_field_rejected() {
+        match Headers::parse(&mut BufReader::new(" folded\r\n\r\n".as_bytes())).await {
+            Ok(_) => panic!("should not pass"),
+            Err(e) => assert_eq!(e, HeadersError::MalformedPart),
+        }
+    }
+
+    #[tokio::test]
+    async fn bare_carriage_return_in_value_rejected() {
+        match Headers::parse(&mut BufReader::new(
+            "X-Evil: a\rSet-Cookie: b\r\n\r\n".as_bytes(),
+        ))
+        .await
+        {
+            Ok(_) => panic!("should not pass"),
+            Err(e) => assert_eq!(e, HeadersError::MalformedFieldValue),
+        }
+    }
+
+    #[test]
+    fn response_splitting_values_rejected() {
+        let mut headers = Headers::new();
+        for value in ["a\r\nSet-Cookie: evil=1", "a\nb", "a\rb", "a\0b"] {
+            assert_eq!(
+                headers.insert("Location", value),
+                Err(HeadersError::MalformedFieldValue)
+            );
+            assert_eq!(
+                headers.append("Location", value),
+                Err(HeadersError::MalformedFieldValue)
+            );
+        }
+        assert!(headers.is_empty());
+        assert!(headers.append("X-Tab", "a\tb").is_ok());
+    }
+
+    fn field_name() -> impl Strategy<Value = String> {
+        "[A-Za-z0-9!#$%&'*+.^_`|~-]{1,20}"
+    }
+
+    fn field_value() -> impl Strategy<Value = String> {
+        "[!-~]([ -~\t]{0,40}[!-~])?"
+    }
+
+    fn parse_bytes(bytes: &[u8]) -> Result<Headers, HeadersError> {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(Headers::parse(&mut BufReader::new(bytes)))
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+            let _ = parse_bytes(&bytes);
+        }
+
+        #[test]
+        fn serialized_headers_parse_back(
+            entries in proptest::collection::vec((field_name(), field_value()), 0..10)
+        ) {
+            let mut headers = Headers::new();
+            for (name, value) in entries.iter() {
+                headers.append(name, value).unwrap();
+            }
+
+            let parsed = parse_bytes(&headers.to_bytes()).unwrap();
+            let parsed_entries: Vec<(&str, &str)> = parsed.iter().collect();
+            let expected: Vec<(&str, &str)> = entries
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect();
+            prop_assert_eq!(parsed_entries, expected);
+        }
+
+        #[test]
+        fn accepted_values_never_split_lines(
+            name in field_name(),
+            value in any::<String>(),
+        ) {
+            let mut headers = Headers::new();
+            if headers.append(&name, &value).is_ok() {
+                let bytes = headers.to_bytes();
+                prop_assert_eq!(bytes.iter().filter(|&&b| b == b'\n').count(), 2);
+                prop_assert_eq!(bytes.iter().filter(|&&b| b == b'\r').count(), 2);
+                prop_assert!(!bytes.contains(&0));
+            }
+        }
+
+        #[test]
+        fn parsed_headers_serialize_safely(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+            if let Ok(headers) = parse_bytes(&bytes) {
+                let serialized = headers.to_bytes();
+                let reparsed = parse_bytes(&serialized).unwrap();
+                prop_assert_eq!(reparsed.len(), headers.len());
+            }
+        }
+    }
 }
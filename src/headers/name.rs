@@ -0,0 +1,328 @@
+#[derive(Clone, Debug)]
+pub struct HeaderName {
+    repr: Repr,
+}
+
+#[derive(Clone, Debug)]
+enum Repr {
+    Standard(Standard, &'static str),
+    Custom(Box<str>),
+}
+
+macro_rules! standard_headers {
+    ($($konst:ident => $canonical:literal, $lower:literal;)*) => {
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        enum Standard {
+            $($konst,)*
+        }
+
+        impl HeaderName {
+            $(pub const $konst: HeaderName = HeaderName {
+                repr: Repr::Standard(Standard::$konst, $canonical),
+            };)*
+        }
+
+        const STANDARD: &[(Standard, &str, &str)] = &[$((Standard::$konst, $canonical, $lower)),*];
+    };
+}
+
+standard_headers! {
+    ACCEPT => "Accept", "accept";
+    ACCEPT_CHARSET => "Accept-Charset", "accept-charset";
+    ACCEPT_ENCODING => "Accept-Encoding", "accept-encoding";
+    ACCEPT_LANGUAGE => "Accept-Language", "accept-language";
+    ACCEPT_RANGES => "Accept-Ranges", "accept-ranges";
+    ACCESS_CONTROL_ALLOW_CREDENTIALS => "Access-Control-Allow-Credentials", "access-control-allow-credentials";
+    ACCESS_CONTROL_ALLOW_HEADERS => "Access-Control-Allow-Headers", "access-control-allow-headers";
+    ACCESS_CONTROL_ALLOW_METHODS => "Access-Control-Allow-Methods", "access-control-allow-methods";
+    ACCESS_CONTROL_ALLOW_ORIGIN => "Access-Control-Allow-Origin", "access-control-allow-origin";
+    ACCESS_CONTROL_EXPOSE_HEADERS => "Access-Control-Expose-Headers", "access-control-expose-headers";
+    ACCESS_CONTROL_MAX_AGE => "Access-Control-Max-Age", "access-control-max-age";
+    ACCESS_CONTROL_REQUEST_HEADERS => "Access-Control-Request-Headers", "access-control-request-headers";
+    ACCESS_CONTROL_REQUEST_METHOD => "Access-Control-Request-Method", "access-control-request-method";
+    AGE => "Age", "age";
+    ALLOW => "Allow", "allow";
+    AUTHORIZATION => "Authorization", "authorization";
+    CACHE_CONTROL => "Cache-Control", "cache-control";
+    CONNECTION => "Connection", "connection";
+    CONTENT_DISPOSITION => "Content-Disposition", "content-disposition";
+    CONTENT_ENCODING => "Content-Encoding", "content-encoding";
+    CONTENT_LANGUAGE => "Content-Language", "content-language";
+    CONTENT_LENGTH => "Content-Length", "content-length";
+    CONTENT_RANGE => "Content-Range", "content-range";
+    CONTENT_TYPE => "Content-Type", "content-type";
+    COOKIE => "Cookie", "cookie";
+    DATE => "Date", "date";
+    ETAG => "ETag", "etag";
+    EXPECT => "Expect", "expect";
+    EXPIRES => "Expires", "expires";
+    FORWARDED => "Forwarded", "forwarded";
+    HOST => "Host", "host";
+    IF_MATCH => "If-Match", "if-match";
+    IF_MODIFIED_SINCE => "If-Modified-Since", "if-modified-since";
+    IF_NONE_MATCH => "If-None-Match", "if-none-match";
+    IF_RANGE => "If-Range", "if-range";
+    IF_UNMODIFIED_SINCE => "If-Unmodified-Since", "if-unmodified-since";
+    KEEP_ALIVE => "Keep-Alive", "keep-alive";
+    LAST_MODIFIED => "Last-Modified", "last-modified";
+    LOCATION => "Location", "location";
+    ORIGIN => "Origin", "origin";
+    PRAGMA => "Pragma", "pragma";
+    RANGE => "Range", "range";
+    REFERER => "Referer", "referer";
+    RETRY_AFTER => "Retry-After", "retry-after";
+    SERVER => "Server", "server";
+    SET_COOKIE => "Set-Cookie", "set-cookie";
+    TE => "TE", "te";
+    TRAILER => "Trailer", "trailer";
+    TRANSFER_ENCODING => "Transfer-Encoding", "transfer-encoding";
+    UPGRADE => "Upgrade", "upgrade";
+    USER_AGENT => "User-Agent", "user-agent";
+    VARY => "Vary", "vary";
+    VIA => "Via", "via";
+    WWW_AUTHENTICATE => "WWW-Authenticate", "www-authenticate";
+    X_FORWARDED_FOR => "X-Forwarded-For", "x-forwarded-for";
+    X_REQUEST_ID => "X-Request-Id", "x-request-id";
+}
+
+const EMPTY: u8 = u8::MAX;
+
+const TABLE: [u8; 256] = {
+    let mut table = [EMPTY; 256];
+    let mut index = 0;
+    while index < STANDARD.len() {
+        let mut slot = hash(STANDARD[index].2.as_bytes()) % table.len();
+        while table[slot] != EMPTY {
+            slot = (slot + 1) % table.len();
+        }
+        table[slot] = index as u8;
+        index += 1;
+    }
+    table
+};
+
+const fn hash(name: &[u8]) -> usize {
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut index = 0;
+    while index < name.len() {
+        hash ^= name[index].to_ascii_lowercase() as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+        index += 1;
+    }
+    hash as usize
+}
+
+fn standard(name: &str) -> Option<(Standard, &'static str)> {
+    let mut slot = hash(name.as_bytes()) % TABLE.len();
+    while TABLE[slot] != EMPTY {
+        let (id, canonical, lower) = STANDARD[TABLE[slot] as usize];
+        if name == canonical {
+            return Some((id, canonical));
+        }
+        if name == lower {
+            return Some((id, lower));
+        }
+        slot = (slot + 1) % TABLE.len();
+    }
+    None
+}
+
+impl HeaderName {
+    pub fn new(name: &str) -> Self {
+        let repr = match standard(name) {
+            Some((id, name)) => Repr::Standard(id, name),
+            None => Repr::Custom(name.into()),
+        };
+        Self { repr }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match &self.repr {
+            Repr::Standard(_, name) => name,
+            Repr::Custom(name) => name,
+        }
+    }
+
+    pub fn is_standard(&self) -> bool {
+        matches!(self.repr, Repr::Standard(..))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.as_str().eq_ignore_ascii_case(name)
+    }
+}
+
+impl PartialEq for HeaderName {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.repr, &other.repr) {
+            (Repr::Standard(a, _), Repr::Standard(b, _)) => a == b,
+            _ => self.matches(other.as_str()),
+        }
+    }
+}
+
+impl Eq for HeaderName {}
+
+impl PartialEq<str> for HeaderName {
+    fn eq(&self, other: &str) -> bool {
+        self.matches(other)
+    }
+}
+
+impl PartialEq<&str> for HeaderName {
+    fn eq(&self, other: &&str) -> bool {
+        self.matches(other)
+    }
+}
+
+impl AsRef<str> for HeaderName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for HeaderName {
+    fn from(name: &str) -> Self {
+        HeaderName::new(name)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HeaderKey<'a> {
+    id: Option<Standard>,
+    name: &'a str,
+}
+
+impl HeaderKey<'_> {
+    #[inline(always)]
+    pub fn matches(&self, field: &HeaderName) -> bool {
+        match (self.id, &field.repr) {
+            (Some(id), Repr::Standard(field, _)) => id == *field,
+            _ => field.as_str().eq_ignore_ascii_case(self.name),
+        }
+    }
+}
+
+pub trait AsHeaderName {
+    fn as_str(&self) -> &str;
+    fn to_header_name(&self) -> HeaderName;
+    fn key(&self) -> HeaderKey<'_>;
+}
+
+impl AsHeaderName for HeaderName {
+    fn as_str(&self) -> &str {
+        HeaderName::as_str(self)
+    }
+
+    fn to_header_name(&self) -> HeaderName {
+        self.clone()
+    }
+
+    fn key(&self) -> HeaderKey<'_> {
+        let id = match self.repr {
+            Repr::Standard(id, _) => Some(id),
+            Repr::Custom(_) => None,
+        };
+        HeaderKey {
+            id,
+            name: self.as_str(),
+        }
+    }
+}
+
+impl AsHeaderName for str {
+    fn as_str(&self) -> &str {
+        self
+    }
+
+    fn to_header_name(&self) -> HeaderName {
+        HeaderName::new(self)
+    }
+
+    fn key(&self) -> HeaderKey<'_> {
+        HeaderKey {
+            id: None,
+            name: self,
+        }
+    }
+}
+
+impl AsHeaderName for String {
+    fn as_str(&self) -> &str {
+        self
+    }
+
+    fn to_header_name(&self) -> HeaderName {
+        HeaderName::new(self)
+    }
+
+    fn key(&self) -> HeaderKey<'_> {
+        self.as_str().key()
+    }
+}
+
+impl<T: AsHeaderName + ?Sized> AsHeaderName for &T {
+    fn as_str(&self) -> &str {
+        T::as_str(self)
+    }
+
+    fn to_header_name(&self) -> HeaderName {
+        T::to_header_name(self)
+    }
+
+    fn key(&self) -> HeaderKey<'_> {
+        T::key(self)
+    }
+}
+
+impl std::fmt::Display for HeaderName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns_canonical_and_lowercase_names() {
+        let canonical = HeaderName::new("Content-Type");
+        let lower = HeaderName::new("content-type");
+        assert!(canonical.is_standard());
+        assert!(lower.is_standard());
+        assert_eq!(canonical.as_str(), "Content-Type");
+        assert_eq!(lower.as_str(), "content-type");
+        assert_eq!(canonical, HeaderName::CONTENT_TYPE);
+        assert_eq!(lower, HeaderName::CONTENT_TYPE);
+    }
+
+    #[test]
+    fn keeps_custom_casing() {
+        let name = HeaderName::new("X-Custom-ID");
+        assert!(!name.is_standard());
+        assert_eq!(name.as_str(), "X-Custom-ID");
+        assert_eq!(name, "x-custom-id");
+
+        let mixed = HeaderName::new("CONTENT-type");
+        assert!(!mixed.is_standard());
+        assert_eq!(mixed, HeaderName::CONTENT_TYPE);
+    }
+
+    #[test]
+    fn lookups_accept_names_and_strings() {
+        let mut headers = crate::headers::Headers::new();
+        headers.append("content-type", "text/plain").unwrap();
+        headers.append(HeaderName::X_REQUEST_ID, "42").unwrap();
+        headers.append("X-Custom-ID", "a").unwrap();
+        assert_eq!(headers.get(HeaderName::CONTENT_TYPE), Some("text/plain"));
+        let custom = HeaderName::new("x-custom-id");
+        assert_eq!(headers.get(&custom), Some("a"));
+        assert!(!custom.is_standard());
+        assert_eq!(headers.get("x-request-id"), Some("42"));
+        assert!(headers.contains(String::from("Content-Type")));
+        assert_eq!(headers.remove(HeaderName::X_REQUEST_ID), ["42"]);
+    }
+}
@@ -0,0 +1,345 @@
+use std::{borrow::Cow, ops::Range};
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use crate::headers::headers::{Headers, HeadersError};
+
+pub const MAX_LINE_LENGTH: usize = 8 * 1024;
+pub const MAX_HEAD_SIZE: usize = 64 * 1024;
+pub const MAX_FIELDS: usize = 100;
+
+#[derive(Debug, PartialEq)]
+pub enum Progress {
+    Complete(usize),
+    Partial,
+}
+
+pub trait Parse {
+    type Output;
+    type Error: From<HeadersError>;
+
+    fn parse(&mut self, buffer: &[u8]) -> Result<Progress, Self::Error>;
+    fn output(&self, buffer: &[u8]) -> Result<Self::Output, Self::Error>;
+}
+
+pub async fn read_from<P: Parse>(
+    reader: &mut BufReader<impl AsyncRead + Unpin>,
+    pending: &mut Vec<u8>,
+    parser: &mut P,
+) -> Result<P::Output, P::Error> {
+    pending.clear();
+    loop {
+        let available = reader
+            .fill_buf()
+            .await
+            .map_err(|_| HeadersError::ReadError)?;
+        if available.is_empty() {
+            return Err(if pending.is_empty() {
+                HeadersError::ReadError.into()
+            } else {
+                HeadersError::MalformedEndOfLine.into()
+            });
+        }
+
+        let read = available.len();
+        if pending.is_empty() {
+            if let Progress::Complete(length) = parser.parse(available)? {
+                let output = parser.output(available);
+                reader.consume(length);
+                return output;
+            }
+            pending.extend_from_slice(available);
+        } else {
+            let previous = pending.len();
+            pending.extend_from_slice(available);
+            if let Progress::Complete(length) = parser.parse(pending)? {
+                reader.consume(length - previous);
+                return parser.output(pending);
+            }
+        }
+        reader.consume(read);
+        if pending.len() > MAX_HEAD_SIZE {
+            return Err(HeadersError::HeadTooLarge.into());
+        }
+    }
+}
+
+pub fn next_line(buffer: &[u8], start: usize) -> Result<Option<Range<usize>>, HeadersError> {
+    let rest = &buffer[start..];
+    let Some(newline) = rest.iter().position(|&b| b == b'\n') else {
+        if rest.len() > MAX_LINE_LENGTH {
+            return Err(HeadersError::LineTooLong);
+        }
+        return Ok(None);
+    };
+    if newline + 1 > MAX_LINE_LENGTH {
+        return Err(HeadersError::LineTooLong);
+    }
+    if newline == 0 || rest[newline - 1] != b'\r' {
+        return Err(HeadersError::MalformedEndOfLine);
+    }
+    Ok(Some(start..start + newline - 1))
+}
+
+pub fn decode_value(value: &[u8]) -> Cow<'_, str> {
+    match std::str::from_utf8(value) {
+        Ok(value) => Cow::Borrowed(value),
+        Err(_) => Cow::Owned(value.iter().map(|&b| b as char).collect()),
+    }
+}
+
+pub fn is_token(bytes: &[u8]) -> bool {
+    !bytes.is_empty()
+        && bytes.iter().all(|&b| {
+            b.is_ascii_alphanumeric()
+                || matches!(
+                    b,
+                    b'!' | b'#'
+                        | b'$'
+                        | b'%'
+                        | b'&'
+                        | b'\''
+                        | b'*'
+                        | b'+'
+                        | b'-'
+                        | b'.'
+                        | b'^'
+                        | b'_'
+                        | b'`'
+                        | b'|'
+                        | b'~'
+                )
+        })
+}
+
+#[derive(Clone, Debug)]
+struct FieldSpan {
+    name: Range<usize>,
+    value: Range<usize>,
+    folded: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Field<'b> {
+    pub name: &'b str,
+    pub value: Cow<'b, [u8]>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FieldParser {
+    position: usize,
+    fields: Vec<FieldSpan>,
+    complete: Option<usize>,
+}
+
+impl FieldParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn starting_at(position: usize) -> Self {
+        Self {
+            position,
+            ..Self::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn fields<'b>(&'b self, buffer: &'b [u8]) -> impl Iterator<Item = Field<'b>> + 'b {
+        self.fields.iter().map(move |field| Field {
+            name: std::str::from_utf8(&buffer[field.name.clone()]).unwrap_or_default(),
+            value: if field.folded {
+                Cow::Owned(unfold(&buffer[field.value.clone()]))
+            } else {
+                Cow::Borrowed(&buffer[field.value.clone()])
+            },
+        })
+    }
+
+    fn parse_line(&mut self, buffer: &[u8], line: Range<usize>) -> Result<(), HeadersError> {
+        let bytes = &buffer[line.clone()];
+        if (bytes.starts_with(b" ") || bytes.starts_with(b"\t"))
+            && let Some(last) = self.fields.last_mut()
+        {
+            check_value(bytes)?;
+            last.value.end = line.end;
+            last.folded = true;
+            return Ok(());
+        }
+
+        let Some(colon) = bytes.iter().position(|&b| b == b':') else {
+            return Err(HeadersError::MalformedPart);
+        };
+        if !is_token(&bytes[..colon]) {
+            return Err(HeadersError::MalformedFieldName);
+        }
+
+        let value = trim(bytes, colon + 1..bytes.len());
+        check_value(&bytes[value.clone()])?;
+        self.fields.push(FieldSpan {
+            name: line.start..line.start + colon,
+            value: line.start + value.start..line.start + value.end,
+            folded: false,
+        });
+        Ok(())
+    }
+}
+
+impl Parse for FieldParser {
+    type Output = Headers;
+    type Error = HeadersError;
+
+    fn parse(&mut self, buffer: &[u8]) -> Result<Progress, HeadersError> {
+        if let Some(length) = self.complete {
+            return Ok(Progress::Complete(length));
+        }
+        while let Some(line) = next_line(buffer, self.position)? {
+            self.position = line.end + 2;
+            if line.is_empty() {
+                self.complete = Some(self.position);
+                return Ok(Progress::Complete(self.position));
+            }
+            self.parse_line(buffer, line)?;
+            if self.fields.len() > MAX_FIELDS {
+                return Err(HeadersError::TooManyFields);
+            }
+        }
+        Ok(Progress::Partial)
+    }
+
+    fn output(&self, buffer: &[u8]) -> Result<Headers, HeadersError> {
+        let mut headers = Headers::with_capacity(self.fields.len());
+        for field in self.fields(buffer) {
+            headers.append(field.name, &decode_value(&field.value))?;
+        }
+        Ok(headers)
+    }
+}
+
+fn check_value(value: &[u8]) -> Result<(), HeadersError> {
+    if value.iter().any(|&b| (b < b' ' && b != b'\t') || b == 0x7f) {
+        return Err(HeadersError::MalformedFieldValue);
+    }
+    Ok(())
+}
+
+fn trim(bytes: &[u8], mut range: Range<usize>) -> Range<usize> {
+    while range.start < range.end && matches!(bytes[range.start], b' ' | b'\t') {
+        range.start += 1;
+    }
+    while range.end > range.start && matches!(bytes[range.end - 1], b' ' | b'\t') {
+        range.end -= 1;
+    }
+    range
+}
+
+fn unfold(value: &[u8]) -> Vec<u8> {
+    let mut unfolded = Vec::with_capacity(value.len());
+    for line in value.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line = &line[trim(line, 0..line.len())];
+        if line.is_empty() {
+            continue;
+        }
+        if !unfolded.is_empty() {
+            unfolded.push(b' ');
+        }
+        unfolded.extend_from_slice(line);
+    }
+    unfolded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fields_in_place() {
+        let buffer = b"Host: localhost\r\nAccept:  */* \r\n\r\nbody";
+        let mut parser = FieldParser::new();
+        assert_eq!(parser.parse(buffer), Ok(Progress::Complete(34)));
+
+        let fields: Vec<Field> = parser.fields(buffer).collect();
+        assert_eq!(fields[0].name, "Host");
+        assert_eq!(fields[0].value, Cow::Borrowed(b"localhost".as_slice()));
+        assert!(matches!(fields[1].value, Cow::Borrowed(b"*/*")));
+    }
+
+    #[test]
+    fn resumes_on_partial_input() {
+        let input = b"Host: localhost\r\nX-Folded: first\r\n second\r\n\r\n";
+        let mut parser = FieldParser::new();
+        for end in 0..input.len() {
+            assert_eq!(parser.parse(&input[..end]), Ok(Progress::Partial));
+        }
+        assert_eq!(parser.parse(input), Ok(Progress::Complete(input.len())));
+
+        let headers = parser.output(input).unwrap();
+        assert_eq!(headers.get("host").unwrap(), "localhost");
+        assert_eq!(headers.get("x-folded").unwrap(), "first second");
+    }
+
+    #[test]
+    fn decodes_obs_text_values() {
+        let input = b"X-Latin: caf\xe9\r\nX-Utf8: caf\xc3\xa9\r\n\r\n";
+        let mut parser = FieldParser::new();
+        assert!(matches!(parser.parse(input), Ok(Progress::Complete(_))));
+
+        let headers = parser.output(input).unwrap();
+        assert_eq!(headers.get("x-latin").unwrap(), "café");
+        assert_eq!(headers.get("x-utf8").unwrap(), "café");
+    }
+
+    #[test]
+    fn rejects_too_many_fields() {
+        let input = "X-A: 1\r\n".repeat(MAX_FIELDS + 1);
+        assert_eq!(
+            FieldParser::new().parse(input.as_bytes()),
+            Err(HeadersError::TooManyFields)
+        );
+    }
+
+    #[tokio::test]
+    async fn reuses_pending_buffer_and_caps_head_size() {
+        let head = "Host: localhost\r\n\r\n".as_bytes();
+        let mut reader = BufReader::with_capacity(4, head);
+        let mut pending = Vec::new();
+        let headers = read_from(&mut reader, &mut pending, &mut FieldParser::new())
+            .await
+            .unwrap();
+        assert_eq!(headers.get("host").unwrap(), "localhost");
+        let capacity = pending.capacity();
+
+        let mut reader = BufReader::with_capacity(4, head);
+        read_from(&mut reader, &mut pending, &mut FieldParser::new())
+            .await
+            .unwrap();
+        assert_eq!(pending.capacity(), capacity);
+
+        let line = format!("X-A: {}\r\n", "a".repeat(MAX_LINE_LENGTH - 16));
+        let oversized = line.repeat(MAX_HEAD_SIZE / line.len() + 1);
+        let mut reader = BufReader::new(oversized.as_bytes());
+        assert_eq!(
+            read_from(&mut reader, &mut pending, &mut FieldParser::new())
+                .await
+                .err(),
+            Some(HeadersError::HeadTooLarge)
+        );
+    }
+
+    #[test]
+    fn rejects_overlong_line() {
+        let input = vec![b'a'; MAX_LINE_LENGTH + 1];
+        assert_eq!(
+            FieldParser::new().parse(&input),
+            Err(HeadersError::LineTooLong)
+        );
+    }
+}
@@ -0,0 +1,714 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::headers::{Headers, headers::HeadersError};
+
+pub trait Header: Sized {
+    fn name() -> &'static str;
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader>;
+    fn encode(&self) -> String;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidHeader {
+    pub name: &'static str,
+}
+
+impl std::fmt::Display for InvalidHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid value for header {}", self.name)
+    }
+}
+
+impl std::error::Error for InvalidHeader {}
+
+impl Headers {
+    pub fn typed_get<H: Header>(&self) -> Result<Option<H>, InvalidHeader> {
+        let values: Vec<&str> = self.get_all(H::name()).collect();
+        if values.is_empty() {
+            return Ok(None);
+        }
+        H::decode(&values).map(Some)
+    }
+
+    pub fn typed_insert<H: Header>(&mut self, header: &H) -> Result<(), HeadersError> {
+        self.insert(H::name(), &header.encode())
+    }
+}
+
+fn invalid<H: Header>() -> InvalidHeader {
+    InvalidHeader { name: H::name() }
+}
+
+fn single<'a, H: Header>(values: &[&'a str]) -> Result<&'a str, InvalidHeader> {
+    match values {
+        [value] => Ok(value.trim()),
+        _ => Err(invalid::<H>()),
+    }
+}
+
+fn list<'a>(values: &[&'a str]) -> impl Iterator<Item = &'a str> {
+    values
+        .iter()
+        .flat_map(|value| split_quoted(value, ','))
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+fn split_quoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if c == separator && !quoted => {
+                parts.push(&value[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn is_token(value: &str) -> bool {
+    crate::headers::parser::is_token(value.as_bytes())
+}
+
+fn unquote(value: &str) -> Option<String> {
+    match value.strip_prefix('"') {
+        Some(rest) => {
+            let inner = rest.strip_suffix('"')?;
+            let mut unquoted = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                if c == '\\' {
+                    unquoted.push(chars.next()?);
+                } else {
+                    unquoted.push(c);
+                }
+            }
+            Some(unquoted)
+        }
+        None if is_token(value) => Some(value.to_string()),
+        None => None,
+    }
+}
+
+fn quote(value: &str) -> String {
+    if is_token(value) {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContentLength(pub u64);
+
+impl Header for ContentLength {
+    fn name() -> &'static str {
+        "Content-Length"
+    }
+
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader> {
+        let mut length = None;
+        for item in values.iter().flat_map(|value| value.split(',')) {
+            let item = item.trim();
+            if item.is_empty() || !item.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid::<Self>());
+            }
+            let parsed = item.parse::<u64>().map_err(|_| invalid::<Self>())?;
+            if length.is_some_and(|length| length != parsed) {
+                return Err(invalid::<Self>());
+            }
+            length = Some(parsed);
+        }
+        length.map(ContentLength).ok_or_else(invalid::<Self>)
+    }
+
+    fn encode(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaType {
+    pub kind: String,
+    pub subtype: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl MediaType {
+    pub fn new(kind: &str, subtype: &str) -> Self {
+        Self {
+            kind: kind.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            params: Vec::new(),
+        }
+    }
+
+    pub fn with_param(mut self, name: &str, value: &str) -> Self {
+        self.params
+            .push((name.to_ascii_lowercase(), value.to_string()));
+        self
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = split_quoted(value, ';').into_iter();
+        let (kind, subtype) = parts.next()?.trim().split_once('/')?;
+        if !is_token(kind) || !is_token(subtype) {
+            return None;
+        }
+
+        let mut media_type = MediaType::new(kind, subtype);
+        for param in parts {
+            let param = param.trim();
+            if param.is_empty() {
+                continue;
+            }
+            let (name, value) = param.split_once('=')?;
+            if !is_token(name) {
+                return None;
+            }
+            media_type = media_type.with_param(name, &unquote(value)?);
+        }
+        Some(media_type)
+    }
+
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.kind, self.subtype)
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    pub fn matches(&self, other: &MediaType) -> bool {
+        (self.kind == "*" || other.kind == "*" || self.kind == other.kind)
+            && (self.subtype == "*" || other.subtype == "*" || self.subtype == other.subtype)
+    }
+}
+
+impl std::fmt::Display for MediaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.kind, self.subtype)?;
+        for (name, value) in self.params.iter() {
+            write!(f, "; {}={}", name, quote(value))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentType(pub MediaType);
+
+impl Header for ContentType {
+    fn name() -> &'static str {
+        "Content-Type"
+    }
+
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader> {
+        MediaType::parse(single::<Self>(values)?)
+            .map(ContentType)
+            .ok_or_else(invalid::<Self>)
+    }
+
+    fn encode(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Host {
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+impl Header for Host {
+    fn name() -> &'static str {
+        "Host"
+    }
+
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader> {
+        let value = single::<Self>(values)?;
+        let (host, port) = if let Some(rest) = value.strip_prefix('[') {
+            let (address, rest) = rest.split_once(']').ok_or_else(invalid::<Self>)?;
+            let port = match rest {
+                "" => None,
+                _ => Some(rest.strip_prefix(':').ok_or_else(invalid::<Self>)?),
+            };
+            (format!("[{}]", address), port)
+        } else {
+            match value.split_once(':') {
+                Some((host, port)) => (host.to_string(), Some(port)),
+                None => (value.to_string(), None),
+            }
+        };
+
+        if host.is_empty()
+            || host
+                .chars()
+                .any(|c| c.is_whitespace() || matches!(c, '/' | '@' | '?' | '#'))
+        {
+            return Err(invalid::<Self>());
+        }
+        let port = match port {
+            Some("") | None => None,
+            Some(port) => Some(port.parse::<u16>().map_err(|_| invalid::<Self>())?),
+        };
+        Ok(Host { host, port })
+    }
+
+    fn encode(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}:{}", self.host, port),
+            None => self.host.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Authorization {
+    Basic { username: String, password: String },
+    Bearer(String),
+    Other { scheme: String, credentials: String },
+}
+
+impl Header for Authorization {
+    fn name() -> &'static str {
+        "Authorization"
+    }
+
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader> {
+        let value = single::<Self>(values)?;
+        let (scheme, credentials) = value.split_once(' ').ok_or_else(invalid::<Self>)?;
+        let credentials = credentials.trim();
+        if !is_token(scheme) || credentials.is_empty() {
+            return Err(invalid::<Self>());
+        }
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = STANDARD
+                .decode(credentials)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(invalid::<Self>)?;
+            let (username, password) = decoded.split_once(':').ok_or_else(invalid::<Self>)?;
+            Ok(Authorization::Basic {
+                username: username.to_string(),
+                password: password.to_string(),
+            })
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            Ok(Authorization::Bearer(credentials.to_string()))
+        } else {
+            Ok(Authorization::Other {
+                scheme: scheme.to_string(),
+                credentials: credentials.to_string(),
+            })
+        }
+    }
+
+    fn encode(&self) -> String {
+        match self {
+            Authorization::Basic { username, password } => {
+                format!(
+                    "Basic {}",
+                    STANDARD.encode(format!("{username}:{password}"))
+                )
+            }
+            Authorization::Bearer(token) => format!("Bearer {token}"),
+            Authorization::Other {
+                scheme,
+                credentials,
+            } => format!("{scheme} {credentials}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheControl {
+    directives: Vec<(String, Option<String>)>,
+}
+
+impl CacheControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn directive(mut self, name: &str, value: Option<&str>) -> Self {
+        self.directives
+            .push((name.to_ascii_lowercase(), value.map(str::to_string)));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Option<&str>> {
+        self.directives
+            .iter()
+            .find(|(directive, _)| directive.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_deref())
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn max_age(&self) -> Option<u64> {
+        self.get("max-age")?.and_then(|value| value.parse().ok())
+    }
+
+    pub fn no_cache(&self) -> bool {
+        self.has("no-cache")
+    }
+
+    pub fn no_store(&self) -> bool {
+        self.has("no-store")
+    }
+}
+
+impl Header for CacheControl {
+    fn name() -> &'static str {
+        "Cache-Control"
+    }
+
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader> {
+        let mut cache_control = CacheControl::new();
+        for item in list(values) {
+            cache_control = match item.split_once('=') {
+                Some((name, value)) if is_token(name) => {
+                    let value = unquote(value.trim()).ok_or_else(invalid::<Self>)?;
+                    cache_control.directive(name, Some(&value))
+                }
+                None if is_token(item) => cache_control.directive(item, None),
+                _ => return Err(invalid::<Self>()),
+            };
+        }
+        if cache_control.directives.is_empty() {
+            return Err(invalid::<Self>());
+        }
+        Ok(cache_control)
+    }
+
+    fn encode(&self) -> String {
+        self.directives
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("{}={}", name, quote(value)),
+                None => name.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptItem {
+    pub media_type: MediaType,
+    pub quality: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accept(pub Vec<AcceptItem>);
+
+impl Accept {
+    pub fn preferred(&self) -> Option<&MediaType> {
+        self.0
+            .iter()
+            .filter(|item| item.quality > 0.0)
+            .fold(None, |best: Option<&AcceptItem>, item| match best {
+                Some(best) if best.quality >= item.quality => Some(best),
+                _ => Some(item),
+            })
+            .map(|item| &item.media_type)
+    }
+
+    pub fn accepts(&self, media_type: &MediaType) -> bool {
+        self.0
+            .iter()
+            .any(|item| item.quality > 0.0 && item.media_type.matches(media_type))
+    }
+}
+
+impl Header for Accept {
+    fn name() -> &'static str {
+        "Accept"
+    }
+
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader> {
+        let mut items = Vec::new();
+        for item in list(values) {
+            let mut media_type = MediaType::parse(item).ok_or_else(invalid::<Self>)?;
+            let quality = match media_type.param("q") {
+                Some(q) => q
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|q| (0.0..=1.0).contains(q))
+                    .ok_or_else(invalid::<Self>)?,
+                None => 1.0,
+            };
+            media_type.params.retain(|(name, _)| name != "q");
+            items.push(AcceptItem {
+                media_type,
+                quality,
+            });
+        }
+        Ok(Accept(items))
+    }
+
+    fn encode(&self) -> String {
+        self.0
+            .iter()
+            .map(|item| {
+                if item.quality < 1.0 {
+                    format!("{};q={}", item.media_type, item.quality)
+                } else {
+                    item.media_type.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Date(pub DateTime<Utc>);
+
+const IMF_FIXDATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+const RFC_850: &str = "%A, %d-%b-%y %H:%M:%S GMT";
+const ASCTIME: &str = "%a %b %e %H:%M:%S %Y";
+
+impl Date {
+    pub fn now() -> Self {
+        Date(Utc::now())
+    }
+}
+
+impl Header for Date {
+    fn name() -> &'static str {
+        "Date"
+    }
+
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader> {
+        let value = single::<Self>(values)?;
+        [IMF_FIXDATE, RFC_850, ASCTIME]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+            .map(|date| Date(date.and_utc()))
+            .ok_or_else(invalid::<Self>)
+    }
+
+    fn encode(&self) -> String {
+        self.0.format(IMF_FIXDATE).to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection(pub Vec<String>);
+
+impl Connection {
+    pub fn contains(&self, option: &str) -> bool {
+        self.0.iter().any(|item| item.eq_ignore_ascii_case(option))
+    }
+}
+
+impl Header for Connection {
+    fn name() -> &'static str {
+        "Connection"
+    }
+
+    fn decode(values: &[&str]) -> Result<Self, InvalidHeader> {
+        let options: Vec<String> = list(values).map(str::to_string).collect();
+        if options.iter().any(|option| !is_token(option)) {
+            return Err(invalid::<Self>());
+        }
+        Ok(Connection(options))
+    }
+
+    fn encode(&self) -> String {
+        self.0.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn round_trip<H: Header + PartialEq + std::fmt::Debug>(header: H, encoded: &str) {
+        assert_eq!(header.encode(), encoded);
+        assert_eq!(H::decode(&[encoded]).unwrap(), header);
+
+        let mut headers = Headers::new();
+        headers.typed_insert(&header).unwrap();
+        assert_eq!(headers.typed_get::<H>().unwrap().unwrap(), header);
+    }
+
+    #[test]
+    fn content_length_round_trip() {
+        round_trip(ContentLength(42), "42");
+    }
+
+    #[test]
+    fn content_length_rejects_conflicting_values() {
+        assert_eq!(
+            ContentLength::decode(&["42, 42"]).unwrap(),
+            ContentLength(42)
+        );
+        assert!(ContentLength::decode(&["42", "43"]).is_err());
+        assert!(ContentLength::decode(&["+42"]).is_err());
+    }
+
+    #[test]
+    fn content_type_round_trip() {
+        round_trip(
+            ContentType(MediaType::new("text", "html").with_param("charset", "utf-8")),
+            "text/html; charset=utf-8",
+        );
+        round_trip(
+            ContentType(MediaType::new("multipart", "form-data").with_param("boundary", "a b")),
+            "multipart/form-data; boundary=\"a b\"",
+        );
+    }
+
+    #[test]
+    fn content_type_parses_case_and_quotes() {
+        let ContentType(media_type) =
+            ContentType::decode(&["Application/JSON ; Charset=\"UTF-8\""]).unwrap();
+        assert_eq!(media_type.essence(), "application/json");
+        assert_eq!(media_type.charset(), Some("UTF-8"));
+        assert!(ContentType::decode(&["json"]).is_err());
+    }
+
+    #[test]
+    fn host_round_trip() {
+        round_trip(
+            Host {
+                host: "localhost".to_string(),
+                port: Some(42069),
+            },
+            "localhost:42069",
+        );
+        round_trip(
+            Host {
+                host: "[::1]".to_string(),
+                port: None,
+            },
+            "[::1]",
+        );
+        assert_eq!(
+            Host::decode(&["localhost:"]).unwrap(),
+            Host {
+                host: "localhost".to_string(),
+                port: None,
+            }
+        );
+        assert_eq!(Host::decode(&["[::1]:"]).unwrap().port, None);
+        assert!(Host::decode(&["localhost:port"]).is_err());
+        assert!(Host::decode(&["a", "b"]).is_err());
+    }
+
+    #[test]
+    fn typed_insert_rejects_invalid_values() {
+        let mut headers = Headers::new();
+        assert!(
+            headers
+                .typed_insert(&Authorization::Bearer(
+                    "token\r\nSet-Cookie: a=1".to_string()
+                ))
+                .is_err()
+        );
+        assert!(headers.get("authorization").is_none());
+    }
+
+    #[test]
+    fn authorization_round_trip() {
+        round_trip(
+            Authorization::Basic {
+                username: "Aladdin".to_string(),
+                password: "open sesame".to_string(),
+            },
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+        );
+        round_trip(
+            Authorization::Bearer("abc.def".to_string()),
+            "Bearer abc.def",
+        );
+        round_trip(
+            Authorization::Other {
+                scheme: "Digest".to_string(),
+                credentials: "username=\"bob\"".to_string(),
+            },
+            "Digest username=\"bob\"",
+        );
+        assert!(Authorization::decode(&["Basic not-base64!"]).is_err());
+    }
+
+    #[test]
+    fn cache_control_round_trip() {
+        let cache_control = CacheControl::new()
+            .directive("no-cache", None)
+            .directive("max-age", Some("60"))
+            .directive("private", Some("Set-Cookie, Authorization"));
+        round_trip(
+            cache_control.clone(),
+            "no-cache, max-age=60, private=\"Set-Cookie, Authorization\"",
+        );
+        assert_eq!(cache_control.max_age(), Some(60));
+        assert!(cache_control.no_cache());
+        assert!(!cache_control.no_store());
+    }
+
+    #[test]
+    fn accept_round_trip() {
+        let accept = Accept(vec![
+            AcceptItem {
+                media_type: MediaType::new("text", "html"),
+                quality: 1.0,
+            },
+            AcceptItem {
+                media_type: MediaType::new("application", "json"),
+                quality: 0.5,
+            },
+        ]);
+        round_trip(accept.clone(), "text/html, application/json;q=0.5");
+        assert_eq!(accept.preferred().unwrap().essence(), "text/html");
+        assert!(accept.accepts(&MediaType::new("application", "json")));
+        assert!(!accept.accepts(&MediaType::new("image", "png")));
+        assert!(Accept::decode(&["text/html;q=2"]).is_err());
+    }
+
+    #[test]
+    fn date_round_trip() {
+        let date = Date(Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap());
+        round_trip(date, "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(
+            Date::decode(&["Sunday, 06-Nov-94 08:49:37 GMT"]).unwrap(),
+            date
+        );
+        assert_eq!(Date::decode(&["Sun Nov  6 08:49:37 1994"]).unwrap(), date);
+    }
+
+    #[test]
+    fn connection_round_trip() {
+        let connection = Connection(vec!["keep-alive".to_string(), "Upgrade".to_string()]);
+        round_trip(connection.clone(), "keep-alive, Upgrade");
+        assert!(connection.contains("upgrade"));
+    }
+
+    #[test]
+    fn typed_get_missing_header() {
+        assert_eq!(Headers::new().typed_get::<ContentLength>(), Ok(None));
+    }
+}
@@ -0,0 +1,11 @@
+#![allow(clippy::module_inception)]
+pub mod auth;
+pub mod cookie;
+pub mod cors;
+pub mod extensions;
+pub mod headers;
+pub mod request;
+pub mod response;
+pub mod router;
+pub mod server;
+pub mod session;
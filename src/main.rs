@@ -1,12 +1,7 @@
-use crate::{
+use http::{
     router::router::{Context, HandlerResult, Router},
     server::Server,
 };
-mod headers;
-mod request;
-mod response;
-mod router;
-mod server;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -1,4 +1,5 @@
 pub mod body;
+pub mod parser;
 pub mod request;
 pub use body::Body;
-pub use request::request_from_reader;
+pub use request::{read_body, read_head, request_from_reader};
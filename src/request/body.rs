@@ -1,4 +1,6 @@
-use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+
+use crate::headers::parser::MAX_LINE_LENGTH;
 
 #[derive(Clone)]
 pub struct Body {
@@ -16,17 +18,57 @@ impl Body {
         let count = length
             .parse::<usize>()
             .map_err(|_| BodyError::InvalidContentLength)?;
+        Body::read(f, count).await
+    }
 
+    pub async fn read(
+        f: &mut BufReader<impl AsyncRead + Unpin>,
+        count: usize,
+    ) -> Result<Body, BodyError> {
         let mut content = vec![0u8; count];
-        let _ = f
-            .read_exact(&mut content)
+        f.read_exact(&mut content)
             .await
-            .map_err(|_| BodyError::MissingData);
+            .map_err(|_| BodyError::MissingData)?;
 
-        if f.buffer().len() > 0 {
-            return Err(BodyError::TooMushData);
+        Ok(Body { content })
+    }
+
+    pub async fn read_chunked(
+        f: &mut BufReader<impl AsyncRead + Unpin>,
+        limit: usize,
+    ) -> Result<Body, BodyError> {
+        let mut content = Vec::new();
+        loop {
+            let line = read_line(f).await?;
+            let size = line.split(|&b| b == b';').next().unwrap_or_default();
+            let size = parse_chunk_size(size.trim_ascii_end())?;
+            if size == 0 {
+                break;
+            }
+            if size > limit - content.len() {
+                return Err(BodyError::TooLarge);
+            }
+            let start = content.len();
+            content.resize(start + size, 0);
+            f.read_exact(&mut content[start..])
+                .await
+                .map_err(|_| BodyError::MissingData)?;
+            if !read_line(f).await?.is_empty() {
+                return Err(BodyError::MalformedChunk);
+            }
         }
 
+        let mut trailers = 0;
+        loop {
+            let line = read_line(f).await?;
+            if line.is_empty() {
+                break;
+            }
+            trailers += line.len();
+            if trailers > MAX_LINE_LENGTH {
+                return Err(BodyError::TooLarge);
+            }
+        }
         Ok(Body { content })
     }
 
@@ -45,13 +87,48 @@ impl Body {
     pub fn len(&self) -> usize {
         self.content.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+}
+
+async fn read_line(f: &mut BufReader<impl AsyncRead + Unpin>) -> Result<Vec<u8>, BodyError> {
+    let mut line = Vec::new();
+    let cap = MAX_LINE_LENGTH as u64 + 2;
+    let read = (&mut *f)
+        .take(cap)
+        .read_until(b'\n', &mut line)
+        .await
+        .map_err(|_| BodyError::MissingData)?;
+    if !line.ends_with(b"\n") {
+        return Err(if read as u64 == cap {
+            BodyError::MalformedChunk
+        } else {
+            BodyError::MissingData
+        });
+    }
+    if !line.ends_with(b"\r\n") {
+        return Err(BodyError::MalformedChunk);
+    }
+    line.truncate(line.len() - 2);
+    Ok(line)
+}
+
+fn parse_chunk_size(size: &[u8]) -> Result<usize, BodyError> {
+    if size.is_empty() || size.len() > 15 || !size.iter().all(u8::is_ascii_hexdigit) {
+        return Err(BodyError::MalformedChunk);
+    }
+    let size = std::str::from_utf8(size).map_err(|_| BodyError::MalformedChunk)?;
+    usize::from_str_radix(size, 16).map_err(|_| BodyError::MalformedChunk)
 }
 
 #[derive(Debug, PartialEq)]
 pub enum BodyError {
     InvalidContentLength,
     MissingData,
-    TooMushData,
+    MalformedChunk,
+    TooLarge,
 }
 
 #[cfg(test)]
@@ -67,10 +144,54 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn too_mush_length_standard_body() {
-        match Body::parse(&mut BufReader::new("hello world!\n".as_bytes()), "12").await {
+    async fn pipelined_data_left_in_reader() {
+        let mut reader = BufReader::new("hello world!\n".as_bytes());
+        match Body::parse(&mut reader, "12").await {
+            Ok(b) => {
+                assert_eq!(b.to_string_lossy(), "hello world!");
+                assert_eq!(reader.buffer(), b"\n");
+            }
+            Err(e) => panic!("dont pass {:?}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_data_body() {
+        match Body::parse(&mut BufReader::new("hello".as_bytes()), "13").await {
             Ok(_) => panic!("should not pass"),
-            Err(e) => assert_eq!(e, BodyError::TooMushData),
+            Err(e) => assert_eq!(e, BodyError::MissingData),
         }
     }
+
+    #[tokio::test]
+    async fn chunked_body() {
+        let mut reader = BufReader::new(
+            "5;ext=1\r\nhello\r\n7\r\n world!\r\n0\r\nX-Trailer: 1\r\n\r\nGET".as_bytes(),
+        );
+        let body = Body::read_chunked(&mut reader, 64).await.unwrap();
+        assert_eq!(body.to_string_lossy(), "hello world!");
+        assert_eq!(reader.buffer(), b"GET");
+    }
+
+    #[tokio::test]
+    async fn chunked_body_limits() {
+        let read = |input: &'static str, limit| async move {
+            Body::read_chunked(&mut BufReader::new(input.as_bytes()), limit)
+                .await
+                .err()
+        };
+        assert_eq!(
+            read("5\r\nhello\r\n0\r\n\r\n", 4).await,
+            Some(BodyError::TooLarge)
+        );
+        assert_eq!(
+            read("fffffffffffffffff\r\n", 4).await,
+            Some(BodyError::MalformedChunk)
+        );
+        assert_eq!(
+            read("5\r\nhelloXX0\r\n\r\n", 64).await,
+            Some(BodyError::MalformedChunk)
+        );
+        assert_eq!(read("5\r\nhel", 64).await, Some(BodyError::MissingData));
+    }
 }
@@ -0,0 +1,161 @@
+use std::ops::Range;
+
+use crate::{
+    headers::{
+        Headers,
+        headers::HeadersError,
+        parser::{Field, FieldParser, Parse, Progress, next_line},
+    },
+    request::request::{RequestLine, RequestLineError},
+};
+
+#[derive(Clone, Debug)]
+struct LineSpan {
+    method: Range<usize>,
+    target: Range<usize>,
+    version: &'static str,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RequestParser {
+    line: Option<LineSpan>,
+    fields: FieldParser,
+}
+
+impl RequestParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn method<'b>(&self, buffer: &'b [u8]) -> Option<&'b str> {
+        self.line
+            .as_ref()
+            .and_then(|line| std::str::from_utf8(&buffer[line.method.clone()]).ok())
+    }
+
+    pub fn target<'b>(&self, buffer: &'b [u8]) -> Option<&'b str> {
+        self.line
+            .as_ref()
+            .and_then(|line| std::str::from_utf8(&buffer[line.target.clone()]).ok())
+    }
+
+    pub fn version(&self) -> Option<&'static str> {
+        self.line.as_ref().map(|line| line.version)
+    }
+
+    pub fn fields<'b>(&'b self, buffer: &'b [u8]) -> impl Iterator<Item = Field<'b>> + 'b {
+        self.fields.fields(buffer)
+    }
+
+    fn parse_line(buffer: &[u8], line: Range<usize>) -> Result<LineSpan, RequestLineError> {
+        let bytes = &buffer[line.clone()];
+        let mut parts = bytes.splitn(3, |&b| b == b' ');
+        let (Some(method), Some(target), Some(version)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(RequestLineError::MalformedPart);
+        };
+
+        if method.is_empty() || !method.iter().all(|b| b.is_ascii_uppercase()) {
+            return Err(RequestLineError::MalformedMethod);
+        }
+
+        if !target.starts_with(b"/") || !target.iter().all(|b| b.is_ascii_graphic()) {
+            return Err(RequestLineError::MalformedTarget);
+        }
+
+        let version = match version {
+            b"HTTP/1.1" => "1.1",
+            b"HTTP/1.0" => "1.0",
+            _ => {
+                let is_well_formed = version.strip_prefix(b"HTTP/").is_some_and(|number| {
+                    number.len() == 3
+                        && number[0].is_ascii_digit()
+                        && number[1] == b'.'
+                        && number[2].is_ascii_digit()
+                });
+                if is_well_formed {
+                    return Err(RequestLineError::BadHTTPVersion);
+                }
+                return Err(RequestLineError::MalformedVersion);
+            }
+        };
+
+        let method_start = line.start;
+        let target_start = method_start + method.len() + 1;
+        Ok(LineSpan {
+            method: method_start..method_start + method.len(),
+            target: target_start..target_start + target.len(),
+            version,
+        })
+    }
+}
+
+impl Parse for RequestParser {
+    type Output = (RequestLine, Headers);
+    type Error = RequestLineError;
+
+    fn parse(&mut self, buffer: &[u8]) -> Result<Progress, RequestLineError> {
+        if self.line.is_none() {
+            let Some(line) = next_line(buffer, 0).map_err(|error| match error {
+                HeadersError::LineTooLong => RequestLineError::LineTooLong,
+                error => error.into(),
+            })?
+            else {
+                return Ok(Progress::Partial);
+            };
+            self.fields = FieldParser::starting_at(line.end + 2);
+            self.line = Some(RequestParser::parse_line(buffer, line)?);
+        }
+        Ok(self.fields.parse(buffer)?)
+    }
+
+    fn output(&self, buffer: &[u8]) -> Result<(RequestLine, Headers), RequestLineError> {
+        let (Some(method), Some(target), Some(version)) =
+            (self.method(buffer), self.target(buffer), self.version())
+        else {
+            return Err(RequestLineError::MalformedPart);
+        };
+        let headers = self.fields.output(buffer)?;
+        Ok((RequestLine::new(version, target, method), headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_request_fed_byte_by_byte() {
+        let input = b"GET /coffee?size=large HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n";
+        let mut parser = RequestParser::new();
+        for end in 0..input.len() {
+            assert_eq!(parser.parse(&input[..end]), Ok(Progress::Partial));
+        }
+        assert_eq!(parser.parse(input), Ok(Progress::Complete(input.len())));
+
+        assert_eq!(parser.method(input), Some("GET"));
+        assert_eq!(parser.target(input), Some("/coffee?size=large"));
+        assert_eq!(parser.version(), Some("1.1"));
+        let names: Vec<&str> = parser.fields(input).map(|field| field.name).collect();
+        assert_eq!(names, ["Host", "Accept"]);
+    }
+
+    #[test]
+    fn rejects_bad_request_line_before_headers_arrive() {
+        let mut parser = RequestParser::new();
+        assert_eq!(
+            parser.parse(b"GET /caf\xe9 HTTP/1.1\r\nHost"),
+            Err(RequestLineError::MalformedTarget)
+        );
+    }
+
+    #[test]
+    fn maps_header_errors() {
+        let mut parser = RequestParser::new();
+        assert_eq!(
+            parser.parse(b"GET / HTTP/1.1\r\nHost localhost\r\n\r\n"),
+            Err(RequestLineError::InvalidHeader)
+        );
+    }
+}
@@ -1,14 +1,24 @@
-use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::io::{AsyncRead, BufReader};
 
-use crate::{headers::headers::Headers, request::body::Body};
+use crate::{
+    cookie::CookieJar,
+    extensions::Extensions,
+    headers::{
+        headers::{Headers, HeadersError},
+        parser::read_from,
+        typed::{Connection, ContentLength},
+    },
+    request::{
+        body::{Body, BodyError},
+        parser::RequestParser,
+    },
+};
 
-const MAX_LINE_LENGTH: usize = 8 * 1024;
-
-#[derive(Clone)]
 pub struct Request {
     pub request_line: RequestLine,
     pub headers: Headers,
     pub body: Option<Body>,
+    pub extensions: Extensions,
 }
 
 #[derive(Clone)]
@@ -28,6 +38,26 @@ impl RequestLine {
     }
 }
 
+impl Request {
+    pub fn cookies(&self) -> CookieJar {
+        CookieJar::from_headers(&self.headers)
+    }
+
+    pub fn keep_alive(&self) -> bool {
+        let connection = self.headers.typed_get::<Connection>().ok().flatten();
+        let has_token = |token: &str| {
+            connection
+                .as_ref()
+                .is_some_and(|connection| connection.contains(token))
+        };
+
+        match self.request_line.http_version.as_str() {
+            "1.0" => has_token("keep-alive"),
+            _ => !has_token("close"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum RequestLineError {
     ReadError,
@@ -36,7 +66,14 @@ pub enum RequestLineError {
     MalformedMethod,
     MalformedTarget,
     BadHTTPVersion,
+    MalformedVersion,
+    InvalidHeader,
     LineTooLong,
+    HeaderFieldsTooLarge,
+    BodyTooLarge,
+    MalformedBody,
+    AmbiguousBodyLength,
+    UnsupportedTransferEncoding,
 }
 
 impl std::fmt::Display for RequestLineError {
@@ -49,80 +86,107 @@ impl std::fmt::Display for RequestLineError {
             }
             Self::MalformedMethod => write!(f, "Malformed method, should be in uppercase only"),
             Self::MalformedTarget => write!(f, "Malformed target, should start with a slash"),
-            Self::BadHTTPVersion => write!(f, "Bad http version only 1.1 supported"),
+            Self::BadHTTPVersion => write!(f, "Bad http version only 1.0 and 1.1 supported"),
+            Self::MalformedVersion => write!(f, "Malformed version, should be HTTP/x.y"),
+            Self::InvalidHeader => write!(f, "Invalid header value"),
             RequestLineError::LineTooLong => write!(f, "Line too long"),
+            Self::HeaderFieldsTooLarge => write!(f, "Header fields exceed the configured limits"),
+            Self::BodyTooLarge => write!(f, "Request body exceeds the configured limit"),
+            Self::MalformedBody => write!(f, "Malformed chunked body"),
+            Self::AmbiguousBodyLength => {
+                write!(f, "Both Transfer-Encoding and Content-Length are present")
+            }
+            Self::UnsupportedTransferEncoding => {
+                write!(f, "Only the chunked transfer coding is supported")
+            }
         }
     }
 }
 
-pub async fn request_from_reader(
-    reader: impl AsyncRead + Unpin,
-) -> Result<Request, RequestLineError> {
-    let mut reader = BufReader::new(reader);
-
-    let mut request_line_buffer = String::new();
+impl std::error::Error for RequestLineError {}
 
-    match reader.read_line(&mut request_line_buffer).await {
-        Ok(0) => return Err(RequestLineError::ReadError),
-        Ok(bytes_read) => {
-            if bytes_read == MAX_LINE_LENGTH && !request_line_buffer.ends_with("\r\n") {
-                return Err(RequestLineError::LineTooLong);
-            }
-
-            if !request_line_buffer.ends_with("\r\n") {
-                return Err(RequestLineError::MalformedEndOfLine);
-            }
+impl From<BodyError> for RequestLineError {
+    fn from(error: BodyError) -> Self {
+        match error {
+            BodyError::TooLarge => Self::BodyTooLarge,
+            BodyError::MalformedChunk => Self::MalformedBody,
+            BodyError::InvalidContentLength => Self::InvalidHeader,
+            BodyError::MissingData => Self::ReadError,
+        }
+    }
+}
 
-            let request_line_clean = request_line_buffer.trim_end();
-            let parts: Vec<&str> = request_line_clean.splitn(3, ' ').collect();
+impl From<HeadersError> for RequestLineError {
+    fn from(error: HeadersError) -> Self {
+        match error {
+            HeadersError::ReadError => Self::ReadError,
+            HeadersError::MalformedEndOfLine => Self::MalformedEndOfLine,
+            HeadersError::LineTooLong
+            | HeadersError::TooManyFields
+            | HeadersError::HeadTooLarge => Self::HeaderFieldsTooLarge,
+            _ => Self::InvalidHeader,
+        }
+    }
+}
 
-            if parts.len() != 3 {
-                return Err(RequestLineError::MalformedPart);
-            }
+pub const MAX_BODY_SIZE: usize = 8 * 1024 * 1024;
 
-            let method = parts[0];
-            let target = parts[1];
-            let version = parts[2];
+pub async fn request_from_reader(
+    reader: impl AsyncRead + Unpin,
+) -> Result<Request, RequestLineError> {
+    let mut reader = BufReader::new(reader);
+    let (request_line, headers) = read_head(&mut reader, &mut Vec::new()).await?;
+    let body = read_body(&mut reader, &headers, MAX_BODY_SIZE).await?;
 
-            if !method.chars().all(|c| c.is_ascii_uppercase()) {
-                return Err(RequestLineError::MalformedMethod);
-            }
+    Ok(Request {
+        request_line,
+        headers,
+        body,
+        extensions: Extensions::new(),
+    })
+}
 
-            if !target.starts_with('/') {
-                return Err(RequestLineError::MalformedTarget);
-            }
+pub async fn read_head(
+    reader: &mut BufReader<impl AsyncRead + Unpin>,
+    buffer: &mut Vec<u8>,
+) -> Result<(RequestLine, Headers), RequestLineError> {
+    read_from(reader, buffer, &mut RequestParser::new()).await
+}
 
-            if version != "HTTP/1.1" {
-                return Err(RequestLineError::BadHTTPVersion);
+pub async fn read_body(
+    reader: &mut BufReader<impl AsyncRead + Unpin>,
+    headers: &Headers,
+    limit: usize,
+) -> Result<Option<Body>, RequestLineError> {
+    let content_length = headers
+        .typed_get::<ContentLength>()
+        .map_err(|_| RequestLineError::InvalidHeader)?;
+    let Some(transfer_encoding) = headers.get_combined("Transfer-Encoding") else {
+        return match content_length {
+            Some(ContentLength(length)) => {
+                let length = usize::try_from(length)
+                    .ok()
+                    .filter(|&length| length <= limit)
+                    .ok_or(RequestLineError::BodyTooLarge)?;
+                Ok(Some(Body::read(reader, length).await?))
             }
+            None => Ok(None),
+        };
+    };
 
-            let headers = Headers::parse(&mut reader)
-                .await
-                .map_err(|_| RequestLineError::ReadError)?;
-
-            let body = if let Some(content_length) = headers.headers.get("content-length") {
-                Some(
-                    Body::parse(&mut reader, content_length)
-                        .await
-                        .map_err(|_| RequestLineError::ReadError)?,
-                )
-            } else {
-                None
-            };
-
-            Ok(Request {
-                request_line: RequestLine::new("1.1", target, method),
-                headers,
-                body,
-            })
-        }
-        Err(_) => Err(RequestLineError::ReadError),
+    if content_length.is_some() {
+        return Err(RequestLineError::AmbiguousBodyLength);
+    }
+    if !transfer_encoding.trim().eq_ignore_ascii_case("chunked") {
+        return Err(RequestLineError::UnsupportedTransferEncoding);
     }
+    Ok(Some(Body::read_chunked(reader, limit).await?))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::io::AsyncReadExt;
 
     #[tokio::test]
     async fn good_get_request_line() {
@@ -156,6 +220,41 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn good_http_1_0_request_line() {
+        match request_from_reader("GET /coffee HTTP/1.0\r\nHost: localhost:42069\r\n\r\n".as_bytes()).await {
+            Ok(r) => {
+                assert_eq!(r.request_line.http_version, "1.0");
+                assert!(!r.keep_alive());
+            },
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn http_1_0_keep_alive_request() {
+        match request_from_reader("GET /coffee HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n".as_bytes()).await {
+            Ok(r) => assert!(r.keep_alive()),
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn http_1_1_connection_close_request() {
+        match request_from_reader("GET /coffee HTTP/1.1\r\nConnection: close\r\n\r\n".as_bytes()).await {
+            Ok(r) => assert!(!r.keep_alive()),
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_version_request_line() {
+        match request_from_reader("GET /coffee HTTPS/1\r\nHost: localhost:42069\r\n\r\n".as_bytes()).await {
+            Ok(_) => panic!("should not pass"),
+            Err(e) => assert_eq!(e, RequestLineError::MalformedVersion),
+        }
+    }
+
     #[tokio::test]
     async fn invalid_get_request_line_with_path() {
         match request_from_reader("GET /coffee HTTP/1.1\nHost: localhost:42069\r\nUser-Agent: curl/7.81.0\r\nAccept: */*\r\n\r\n".as_bytes()).await {
@@ -184,6 +283,59 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn pipelined_requests_parsed_in_order() {
+        let mut reader = BufReader::new("POST /coffee HTTP/1.1\r\nContent-Length: 5\r\n\r\nhelloGET /tea HTTP/1.1\r\nHost: localhost:42069\r\n\r\n".as_bytes());
+
+        let mut buffer = Vec::new();
+        let (first, headers) = read_head(&mut reader, &mut buffer).await.unwrap();
+        let body = read_body(&mut reader, &headers, MAX_BODY_SIZE).await.unwrap();
+        assert_eq!(first.request_target, "/coffee");
+        assert_eq!(body.unwrap().to_string_lossy(), "hello");
+
+        let (second, headers) = read_head(&mut reader, &mut buffer).await.unwrap();
+        assert_eq!(second.request_target, "/tea");
+        assert!(read_body(&mut reader, &headers, MAX_BODY_SIZE).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn obs_text_header_value_request() {
+        match request_from_reader(b"GET /coffee HTTP/1.1\r\nX-Origin: M\xfcnchen\r\n\r\n".as_slice()).await {
+            Ok(r) => assert_eq!(r.headers.get("x-origin").unwrap(), "München"),
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn head_split_across_reads() {
+        let reader = b"GET /coffee HT".chain(b"TP/1.1\r\nHost: local".as_slice()).chain(b"host\r\n\r".as_slice()).chain(b"\nGET".as_slice());
+        let mut reader = BufReader::new(reader);
+        let (request_line, headers) = read_head(&mut reader, &mut Vec::new()).await.unwrap();
+        assert_eq!(request_line.request_target, "/coffee");
+        assert_eq!(headers.get("host").unwrap(), "localhost");
+        assert_eq!(reader.buffer(), b"GET");
+    }
+
+    #[tokio::test]
+    async fn cookie_header_request() {
+        match request_from_reader("GET /coffee HTTP/1.1\r\nCookie: id=a3fWa; theme=dark\r\n\r\n".as_bytes()).await {
+            Ok(r) => {
+                let cookies = r.cookies();
+                assert_eq!(cookies.get("id"), Some("a3fWa"));
+                assert_eq!(cookies.get("theme"), Some("dark"));
+            },
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn conflicting_content_length_request() {
+        match request_from_reader("POST /coffee HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\nhello!".as_bytes()).await {
+            Ok(_) => panic!("should not pass"),
+            Err(e) => assert_eq!(e, RequestLineError::InvalidHeader),
+        }
+    }
+
     #[tokio::test]
     async fn invalid_number_of_part_in_request_line() {
         match  request_from_reader("/coffee HTTP/1.1\r\nHost: localhost:42069\r\nUser-Agent: curl/7.81.0\r\nAccept: */*\r\n\r\n".as_bytes()).await {
@@ -1,2 +1,8 @@
+pub mod builder;
+pub mod into_response;
 pub mod response;
-pub use response::{Response, Status};
+pub mod status;
+pub use builder::ResponseBuilder;
+pub use into_response::IntoResponse;
+pub use response::{Response, Version};
+pub use status::{InvalidStatus, Status};
@@ -0,0 +1,64 @@
+use crate::{
+    headers::headers::HeadersError,
+    request::Body,
+    response::{
+        response::{Response, Version, detect_content_type},
+        status::Status,
+    },
+};
+
+pub struct ResponseBuilder {
+    response: Response,
+    error: Option<HeadersError>,
+}
+
+impl ResponseBuilder {
+    pub fn new() -> Self {
+        Self {
+            response: Response::new(),
+            error: None,
+        }
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.response.status(status);
+        self
+    }
+
+    pub fn version(mut self, version: Version) -> Self {
+        self.response.version(version);
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        if self.error.is_none()
+            && let Err(e) = self.response.append_header(name, value)
+        {
+            self.error = Some(e);
+        }
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Result<Response, HeadersError> {
+        let body = body.into();
+        if !self.response.headers().contains("Content-Type") {
+            self.response
+                .set_header("Content-Type", detect_content_type(&body))?;
+        }
+        self.response.set_body(Body::new(body));
+        self.build()
+    }
+
+    pub fn build(self) -> Result<Response, HeadersError> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.response),
+        }
+    }
+}
+
+impl Default for ResponseBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
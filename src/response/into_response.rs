@@ -0,0 +1,86 @@
+use crate::response::{Response, Status};
+
+pub trait IntoResponse {
+    fn into_response(self) -> Response;
+}
+
+impl IntoResponse for Response {
+    fn into_response(self) -> Response {
+        self
+    }
+}
+
+impl IntoResponse for Status {
+    fn into_response(self) -> Response {
+        let mut response = Response::new();
+        response.status(self);
+        response
+    }
+}
+
+impl IntoResponse for () {
+    fn into_response(self) -> Response {
+        Status::NO_CONTENT.into_response()
+    }
+}
+
+impl IntoResponse for String {
+    fn into_response(self) -> Response {
+        let mut response = self.into_bytes().into_response();
+        response
+            .set_header("Content-Type", "text/plain; charset=utf-8")
+            .unwrap();
+        response
+    }
+}
+
+impl IntoResponse for &'static str {
+    fn into_response(self) -> Response {
+        self.to_string().into_response()
+    }
+}
+
+impl IntoResponse for Vec<u8> {
+    fn into_response(self) -> Response {
+        let mut response = Response::new();
+        response.body(self);
+        response
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for (Status, T) {
+    fn into_response(self) -> Response {
+        let mut response = self.1.into_response();
+        response.status(self.0);
+        response
+    }
+}
+
+impl<T: IntoResponse, E: IntoResponse> IntoResponse for Result<T, E> {
+    fn into_response(self) -> Response {
+        match self {
+            Ok(value) => value.into_response(),
+            Err(error) => error.into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_plain_text_even_when_they_look_like_json() {
+        let response = String::from("42").into_response();
+        assert_eq!(
+            response.headers().get("content-type"),
+            Some("text/plain; charset=utf-8")
+        );
+        assert_eq!(response.body_bytes(), b"42");
+
+        let response: Result<&str, Status> = Err(Status::CONFLICT);
+        assert_eq!(response.into_response().status_code(), 409);
+        assert_eq!((Status::CREATED, "made").into_response().status_code(), 201);
+        assert_eq!(().into_response().status_code(), 204);
+    }
+}
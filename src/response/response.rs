@@ -1,14 +1,19 @@
-use chrono::Local;
 use flate2::{Compression, write::GzEncoder};
 use serde_json::Value;
-use std::io::Write;
+use std::io::{IoSlice, Write};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::warn;
 
-use crate::{headers::Headers, request::Body};
+use crate::{
+    cookie::{Cookie, CookieError},
+    headers::{Headers, headers::HeadersError, typed::Date},
+    request::Body,
+    response::{builder::ResponseBuilder, status::Status},
+};
 
 #[derive(Clone)]
 pub struct Response {
     response_line: ResponseLine,
-    content: Vec<u8>,
     headers: Headers,
     body: Option<Body>,
 }
@@ -16,56 +21,224 @@ pub struct Response {
 impl Response {
     pub fn new() -> Self {
         let mut response = Self {
-            content: Vec::new(),
             headers: Headers::new(),
             response_line: ResponseLine {
                 version: Version::OneDotOne,
-                status: Status::Ok,
+                status: Status::OK,
             },
             body: None,
         };
-        response.set_header("Server", "rust");
+        response.headers.insert("Server", "rust").unwrap();
         response
     }
 
-    pub fn set_header(&mut self, key: &str, value: &str) {
-        self.headers.set(key, value).unwrap();
+    pub fn redirect(status: Status, location: &str) -> Result<Self, HeadersError> {
+        let mut response = Self::new();
+        response.headers.insert("Location", location)?;
+        response.status(status);
+        Ok(response)
     }
 
-    pub fn status(&mut self, status: Status) -> Self {
+    pub fn moved_permanently(location: &str) -> Result<Self, HeadersError> {
+        Self::redirect(Status::MOVED_PERMANENTLY, location)
+    }
+
+    pub fn found(location: &str) -> Result<Self, HeadersError> {
+        Self::redirect(Status::FOUND, location)
+    }
+
+    pub fn see_other(location: &str) -> Result<Self, HeadersError> {
+        Self::redirect(Status::SEE_OTHER, location)
+    }
+
+    pub fn temporary_redirect(location: &str) -> Result<Self, HeadersError> {
+        Self::redirect(Status::TEMPORARY_REDIRECT, location)
+    }
+
+    pub fn permanent_redirect(location: &str) -> Result<Self, HeadersError> {
+        Self::redirect(Status::PERMANENT_REDIRECT, location)
+    }
+
+    pub fn set_header(&mut self, key: &str, value: &str) -> Result<(), HeadersError> {
+        self.headers.insert(key, value)
+    }
+
+    pub fn append_header(&mut self, key: &str, value: &str) -> Result<(), HeadersError> {
+        self.headers.append(key, value)
+    }
+
+    pub fn add_cookie(&mut self, cookie: &Cookie) -> Result<(), CookieError> {
+        cookie.validate()?;
+        self.headers
+            .append("Set-Cookie", &cookie.to_string())
+            .map_err(|_| CookieError::InvalidValue)
+    }
+
+    pub fn remove_cookie(&mut self, cookie: Cookie) -> Result<(), CookieError> {
+        self.add_cookie(&cookie.removal())
+    }
+
+    pub fn builder() -> ResponseBuilder {
+        ResponseBuilder::new()
+    }
+
+    pub fn version(&mut self, version: Version) {
+        self.response_line.version = version;
+    }
+
+    pub fn status(&mut self, status: Status) -> &mut Self {
         self.response_line.status = status;
-        self.clone()
+        self
     }
 
     pub fn body(&mut self, body: Vec<u8>) {
-        let content_type = if serde_json::from_slice::<Value>(&body).is_ok() {
-            "application/json"
-        } else {
-            if let Some(kind) = infer::get(&body) {
-                kind.mime_type()
-            } else {
-                "text/plain; charset=utf-8"
-            }
-        };
-        self.set_header("Content-type", content_type);
+        if !self.headers.contains("Content-Type") {
+            self.headers
+                .insert("Content-Type", detect_content_type(&body))
+                .unwrap();
+        }
         self.body = Some(Body::new(body));
     }
 
-    pub fn send(&mut self, accept_encoding: Option<&String>) -> Vec<u8> {
-        self.content.append(&mut Vec::from(
-            format!("{}", &mut self.response_line.version).as_bytes(),
-        ));
-        self.content.append(&mut Vec::from(
-            format!("{}", &mut self.response_line.status).as_bytes(),
-        ));
-        let now = Local::now();
-        self.auto_compress(accept_encoding).unwrap();
-        self.headers.set("Date", now.to_rfc2822().as_str()).unwrap();
-        self.content.append(&mut self.headers.to_bytes());
-        if let Some(body) = &self.body {
-            self.content.append(&mut Vec::from(body.as_bytes()));
+    pub(crate) fn set_body(&mut self, body: Body) {
+        self.body = Some(body);
+    }
+
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    pub fn status_code(&self) -> u16 {
+        self.response_line.status.as_u16()
+    }
+
+    pub fn body_bytes(&self) -> &[u8] {
+        self.body.as_ref().map_or(&[], |body| body.as_bytes())
+    }
+
+    pub fn body_allowed(&self) -> bool {
+        let status = &self.response_line.status;
+        !status.is_informational() && !matches!(status.as_u16(), 204 | 304)
+    }
+
+    pub fn is_chunked(&self) -> bool {
+        self.headers
+            .get_combined("Transfer-Encoding")
+            .and_then(|codings| {
+                codings
+                    .rsplit(',')
+                    .next()
+                    .map(|last| last.trim().eq_ignore_ascii_case("chunked"))
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn prepare(&mut self, accept_encoding: Option<&str>) -> Result<(), HeadersError> {
+        if !self.headers.contains("Content-Encoding")
+            && let Err(e) = self.auto_compress(accept_encoding)
+        {
+            warn!("Sending the body uncompressed: {}", e);
+        }
+        self.headers.typed_insert(&Date::now())?;
+
+        let status = self.status_code();
+        if self.response_line.status.is_informational() || status == 204 {
+            self.headers.remove("Content-Length");
+            self.headers.remove("Transfer-Encoding");
+            return Ok(());
         }
-        self.content.clone()
+        if status == 304 {
+            return Ok(());
+        }
+
+        if self.headers.contains("Transfer-Encoding") {
+            if self.is_chunked() && self.response_line.version == Version::OneDotOne {
+                self.headers.remove("Content-Length");
+                return Ok(());
+            }
+            self.headers.remove("Transfer-Encoding");
+        }
+        if self.body.is_some() || !self.headers.contains("Content-Length") {
+            let length = self.body_bytes().len();
+            self.headers
+                .insert("Content-Length", length.to_string().as_str())?;
+        }
+        Ok(())
+    }
+
+    pub fn send(&mut self, accept_encoding: Option<&str>) -> Result<Vec<u8>, HeadersError> {
+        self.serialize(accept_encoding, true)
+    }
+
+    pub fn send_head(&mut self, accept_encoding: Option<&str>) -> Result<Vec<u8>, HeadersError> {
+        self.serialize(accept_encoding, false)
+    }
+
+    pub async fn write_to<W>(
+        &mut self,
+        writer: &mut W,
+        accept_encoding: Option<&str>,
+        include_body: bool,
+    ) -> std::io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let head = self
+            .encode_head(accept_encoding)
+            .map_err(std::io::Error::other)?;
+        let mut chunk_size = String::new();
+        let body = self.frame_body(&mut chunk_size, include_body);
+
+        let mut slices: Vec<IoSlice> = std::iter::once(head.as_slice())
+            .chain(body)
+            .filter(|part| !part.is_empty())
+            .map(IoSlice::new)
+            .collect();
+        let mut remaining = slices.as_mut_slice();
+        while !remaining.is_empty() {
+            let written = writer.write_vectored(remaining).await?;
+            if written == 0 {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            IoSlice::advance_slices(&mut remaining, written);
+        }
+        writer.flush().await
+    }
+
+    fn serialize(
+        &mut self,
+        accept_encoding: Option<&str>,
+        include_body: bool,
+    ) -> Result<Vec<u8>, HeadersError> {
+        let mut content = self.encode_head(accept_encoding)?;
+        let mut chunk_size = String::new();
+        for part in self.frame_body(&mut chunk_size, include_body) {
+            content.extend_from_slice(part);
+        }
+        Ok(content)
+    }
+
+    fn encode_head(&mut self, accept_encoding: Option<&str>) -> Result<Vec<u8>, HeadersError> {
+        self.prepare(accept_encoding)?;
+        let mut head = self.response_line.to_string().into_bytes();
+        head.append(&mut self.headers.to_bytes());
+        Ok(head)
+    }
+
+    fn frame_body<'a>(&'a self, chunk_size: &'a mut String, include_body: bool) -> [&'a [u8]; 3] {
+        if !include_body || !self.body_allowed() {
+            return [&[]; 3];
+        }
+
+        let body = self.body_bytes();
+        if !self.is_chunked() {
+            return [body, &[], &[]];
+        }
+        if body.is_empty() {
+            return [b"0\r\n\r\n", &[], &[]];
+        }
+        *chunk_size = format!("{:x}\r\n", body.len());
+        [chunk_size.as_bytes(), body, b"\r\n0\r\n\r\n"]
     }
 
     fn compress_gzip(&mut self) -> Result<(), std::io::Error> {
@@ -73,9 +246,9 @@ impl Response {
             let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
             encoder.write_all(body.as_bytes())?;
             let compressed = encoder.finish()?;
-            self.headers.set("Content-Encoding", "gzip").unwrap();
+            self.headers.insert("Content-Encoding", "gzip").unwrap();
             self.headers
-                .set("Content-length", format!("{}", &compressed.len()).as_str())
+                .insert("Content-Length", format!("{}", &compressed.len()).as_str())
                 .unwrap();
             body.set(compressed);
         }
@@ -85,7 +258,7 @@ impl Response {
     pub fn should_compress(&self) -> bool {
         if let Some(content_type) = self.headers.get("content-type") {
             matches!(
-                content_type.as_str(),
+                content_type,
                 "text/html"
                     | "text/css"
                     | "application/javascript"
@@ -97,99 +270,393 @@ impl Response {
         }
     }
 
-    pub fn auto_compress(
-        &mut self,
-        accept_encoding: Option<&String>,
-    ) -> Result<(), std::io::Error> {
-        if let Some(encoding) = accept_encoding {
-            if encoding.contains("gzip") && self.should_compress() {
-                self.compress_gzip()?;
-            }
+    pub fn auto_compress(&mut self, accept_encoding: Option<&str>) -> Result<(), std::io::Error> {
+        if let Some(encoding) = accept_encoding
+            && encoding.contains("gzip")
+            && self.should_compress()
+        {
+            self.compress_gzip()?;
         }
         Ok(())
     }
 }
 
+pub(crate) fn detect_content_type(body: &[u8]) -> &'static str {
+    if serde_json::from_slice::<Value>(body).is_ok() {
+        "application/json"
+    } else if let Some(kind) = infer::get(body) {
+        kind.mime_type()
+    } else {
+        "text/plain; charset=utf-8"
+    }
+}
+
+impl Default for Response {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Clone)]
 pub struct ResponseLine {
     version: Version,
     status: Status,
 }
 
-#[derive(Clone)]
+impl std::fmt::Display for ResponseLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}\r\n", self.version, self.status)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Version {
+    OneDotZero,
     OneDotOne,
 }
 
+impl Version {
+    pub fn from_request(http_version: &str) -> Self {
+        match http_version {
+            "1.0" => Self::OneDotZero,
+            _ => Self::OneDotOne,
+        }
+    }
+}
+
 impl std::fmt::Display for Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            Self::OneDotOne => write!(f, "HTTP/1.1 "),
+            Self::OneDotZero => write!(f, "HTTP/1.0"),
+            Self::OneDotOne => write!(f, "HTTP/1.1"),
         }
     }
 }
 
-#[derive(Clone)]
-pub enum Status {
-    // 2xx Success
-    Ok,
-    Created,
-    Accepted,
-    NoContent,
-    PartialContent,
-
-    // 3xx Redirection
-    MovedPermanently,
-    Found,
-    NotModified,
-
-    // 4xx Client Errors
-    BadRequest,
-    Unauthorized,
-    Forbidden,
-    NotFound,
-    MethodNotAllowed,
-    Conflict,
-    UnprocessableContent,
-    TooManyRequests,
-
-    // 5xx Server Errors
-    InternalServerError,
-    NotImplemented,
-    ServiceUnavailable,
-    GatewayTimeout,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
 
-impl std::fmt::Display for Status {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match *self {
-            // 2xx
-            Self::Ok => write!(f, "200 OK\r\n"),
-            Self::Created => write!(f, "201 Created\r\n"),
-            Self::Accepted => write!(f, "202 Accepted\r\n"),
-            Self::NoContent => write!(f, "204 No Content\r\n"),
-            Self::PartialContent => write!(f, "206 Partial Content"),
-
-            // 3xx
-            Self::MovedPermanently => write!(f, "301 Moved Permanently\r\n"),
-            Self::Found => write!(f, "302 Found\r\n"),
-            Self::NotModified => write!(f, "304 Not Modified\r\n"),
-
-            // 4xx
-            Self::BadRequest => write!(f, "400 Bad Request\r\n"),
-            Self::Unauthorized => write!(f, "401 Unauthorized\r\n"),
-            Self::Forbidden => write!(f, "403 Forbidden\r\n"),
-            Self::NotFound => write!(f, "404 Not Found\r\n"),
-            Self::MethodNotAllowed => write!(f, "405 Method Not Allowed\r\n"),
-            Self::Conflict => write!(f, "409 Conflict\r\n"),
-            Self::UnprocessableContent => write!(f, "422 Unprocessable Content\r\n"),
-            Self::TooManyRequests => write!(f, "429 Too Many Requests\r\n"),
-
-            // 5xx
-            Self::InternalServerError => write!(f, "500 Internal Server Error\r\n"),
-            Self::NotImplemented => write!(f, "501 Not Implemented\r\n"),
-            Self::ServiceUnavailable => write!(f, "503 Service Unavailable\r\n"),
-            Self::GatewayTimeout => write!(f, "504 Gateway Timeout\r\n"),
+    #[test]
+    fn cookies_are_separate_header_lines() {
+        let mut response = Response::new();
+        response
+            .add_cookie(&Cookie::new("id", "a3fWa").path("/").http_only(true))
+            .unwrap();
+        response
+            .remove_cookie(Cookie::new("theme", "dark").path("/"))
+            .unwrap();
+
+        let values: Vec<&str> = response.headers().get_all("set-cookie").collect();
+        assert_eq!(
+            values,
+            [
+                "id=a3fWa; Path=/; HttpOnly",
+                "theme=; Path=/; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT"
+            ]
+        );
+        assert!(
+            String::from_utf8(response.send(None).unwrap())
+                .unwrap()
+                .contains("Set-Cookie: id=a3fWa; Path=/; HttpOnly\r\nSet-Cookie: theme=;")
+        );
+    }
+
+    #[test]
+    fn redirects_set_status_and_location() {
+        let cases = [
+            (Response::moved_permanently("/new"), 301),
+            (Response::found("/new"), 302),
+            (Response::see_other("/new"), 303),
+            (Response::temporary_redirect("/new"), 307),
+            (Response::permanent_redirect("/new"), 308),
+        ];
+        for (response, status) in cases {
+            let response = response.unwrap();
+            assert_eq!(response.status_code(), status);
+            assert_eq!(response.headers().get("location"), Some("/new"));
+        }
+        assert!(Response::found("/new\r\nSet-Cookie: x=1").is_err());
+    }
+
+    #[test]
+    fn status_line_is_serialized_once() {
+        let mut response = Response::new();
+        response.status(Status::PARTIAL_CONTENT);
+        assert!(
+            response
+                .send(None)
+                .unwrap()
+                .starts_with(b"HTTP/1.1 206 Partial Content\r\nServer: rust\r\n")
+        );
+
+        let mut response = Response::new();
+        response.version(Version::OneDotZero);
+        response.status(
+            Status::from_u16(299)
+                .unwrap()
+                .with_reason("Mostly Fine")
+                .unwrap(),
+        );
+        assert!(
+            response
+                .send(None)
+                .unwrap()
+                .starts_with(b"HTTP/1.0 299 Mostly Fine\r\n")
+        );
+    }
+
+    fn head_and_body(bytes: &[u8]) -> (String, &[u8]) {
+        let end = bytes.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        (
+            String::from_utf8(bytes[..end].to_vec()).unwrap(),
+            &bytes[end..],
+        )
+    }
+
+    #[test]
+    fn sets_content_length_for_plain_bodies() {
+        let mut response = Response::builder().body("hello").unwrap();
+        let bytes = response.send(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(head.contains("Content-Length: 5\r\n"));
+        assert_eq!(body, b"hello");
+
+        let mut response = Response::new();
+        let bytes = response.send(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(head.contains("Content-Length: 0\r\n"));
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn omits_framing_for_bodiless_statuses() {
+        let mut response = Response::builder()
+            .status(Status::NO_CONTENT)
+            .header("Content-Length", "12")
+            .body("ignored")
+            .unwrap();
+        let bytes = response.send(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(!head.contains("Content-Length"));
+        assert!(body.is_empty());
+
+        let mut response = Response::builder()
+            .status(Status::NOT_MODIFIED)
+            .header("Content-Length", "42")
+            .build()
+            .unwrap();
+        let bytes = response.send(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(head.contains("Content-Length: 42\r\n"));
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn head_responses_keep_length_without_body() {
+        let mut response = Response::builder().body("hello").unwrap();
+        let bytes = response.send_head(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(head.contains("Content-Length: 5\r\n"));
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn chunked_bodies_are_framed() {
+        let mut response = Response::builder()
+            .header("Transfer-Encoding", "chunked")
+            .header("Content-Length", "3")
+            .body("hello world")
+            .unwrap();
+        let bytes = response.send(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(!head.contains("Content-Length"));
+        assert_eq!(body, b"b\r\nhello world\r\n0\r\n\r\n");
+
+        let mut response = Response::builder()
+            .version(Version::OneDotZero)
+            .header("Transfer-Encoding", "chunked")
+            .body("hello world")
+            .unwrap();
+        let bytes = response.send(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(!head.contains("Transfer-Encoding"));
+        assert!(head.contains("Content-Length: 11\r\n"));
+        assert_eq!(body, b"hello world");
+    }
+
+    #[test]
+    fn unframed_transfer_codings_are_dropped() {
+        let mut response = Response::builder()
+            .header("Transfer-Encoding", "gzip")
+            .body("hello")
+            .unwrap();
+        let bytes = response.send(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(!head.contains("Transfer-Encoding"));
+        assert!(head.contains("Content-Length: 5\r\n"));
+        assert_eq!(body, b"hello");
+
+        let mut response = Response::builder()
+            .header("Transfer-Encoding", "chunked, gzip")
+            .body("hello")
+            .unwrap();
+        let bytes = response.send(None).unwrap();
+        let (head, body) = head_and_body(&bytes);
+        assert!(!head.contains("Transfer-Encoding"));
+        assert!(head.contains("Content-Length: 5\r\n"));
+        assert_eq!(body, b"hello");
+    }
+
+    #[test]
+    fn send_is_idempotent() {
+        let mut response = Response::builder()
+            .header("Content-Type", "text/plain")
+            .body("a".repeat(512))
+            .unwrap();
+        let first = response.send(Some("gzip")).unwrap();
+        let second = response.send(Some("gzip")).unwrap();
+        let (first_head, first_body) = head_and_body(&first);
+        let (second_head, second_body) = head_and_body(&second);
+        assert_eq!(first_head.matches("Content-Encoding").count(), 1);
+        assert_eq!(second_head.matches("Content-Encoding").count(), 1);
+        assert_eq!(first_body, second_body);
+        assert!(first_head.contains(&format!("Content-Length: {}\r\n", first_body.len())));
+        assert_eq!(first.len(), second.len());
+    }
+
+    #[test]
+    fn builder_keeps_explicit_content_type_and_reports_errors() {
+        let response = Response::builder()
+            .status(Status::CREATED)
+            .header("Content-Type", "text/csv")
+            .header("Set-Cookie", "a=1")
+            .header("Set-Cookie", "b=2")
+            .body("a,b\n1,2")
+            .unwrap();
+        assert_eq!(response.status_code(), 201);
+        assert_eq!(response.headers().get("content-type"), Some("text/csv"));
+        assert_eq!(response.headers().get_all("set-cookie").count(), 2);
+
+        let response = Response::builder().body(r#"{"ok":true}"#).unwrap();
+        assert_eq!(
+            response.headers().get("content-type"),
+            Some("application/json")
+        );
+
+        let mut response = Response::new();
+        response.set_header("Content-Type", "text/csv").unwrap();
+        response.body(br#"{"ok":true}"#.to_vec());
+        assert_eq!(response.headers().get("content-type"), Some("text/csv"));
+
+        assert!(
+            Response::builder()
+                .header("X-Bad", "a\r\nb")
+                .header("X-Fine", "ok")
+                .build()
+                .is_err()
+        );
+    }
+
+    struct ShortWriter {
+        written: Vec<u8>,
+        limit: usize,
+        calls: usize,
+    }
+
+    impl AsyncWrite for ShortWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.poll_write_vectored(cx, &[IoSlice::new(buf)])
+        }
+
+        fn poll_write_vectored(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<std::io::Result<usize>> {
+            let mut budget = self.limit;
+            for buf in bufs {
+                let taken = budget.min(buf.len());
+                self.written.extend_from_slice(&buf[..taken]);
+                budget -= taken;
+            }
+            self.calls += 1;
+            Poll::Ready(Ok(self.limit - budget))
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            true
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn write_to_resumes_after_short_writes() {
+        let mut response = Response::builder()
+            .header("Transfer-Encoding", "chunked")
+            .body("hello world")
+            .unwrap();
+        for limit in [1, 7, 64] {
+            let mut writer = ShortWriter {
+                written: Vec::new(),
+                limit,
+                calls: 0,
+            };
+            response.write_to(&mut writer, None, true).await.unwrap();
+            let sent = response.send(None).unwrap();
+            let (head, body) = head_and_body(&writer.written);
+            let (sent_head, sent_body) = head_and_body(&sent);
+            assert_eq!(body, sent_body);
+            assert_eq!(head.len(), sent_head.len());
+            assert_eq!(writer.calls, writer.written.len().div_ceil(limit));
+        }
+    }
+
+    #[tokio::test]
+    async fn write_to_matches_send() {
+        let without_date = |bytes: &[u8]| {
+            String::from_utf8(bytes.to_vec())
+                .unwrap()
+                .split("\r\n")
+                .filter(|line| !line.starts_with("Date:"))
+                .collect::<Vec<_>>()
+                .join("\r\n")
+        };
+        for chunked in [false, true] {
+            let mut builder = Response::builder();
+            if chunked {
+                builder = builder.header("Transfer-Encoding", "chunked");
+            }
+            let mut response = builder.body(vec![b'x'; 100_000]).unwrap();
+
+            let mut written = Vec::new();
+            response.write_to(&mut written, None, true).await.unwrap();
+            assert_eq!(
+                without_date(&written),
+                without_date(&response.send(None).unwrap())
+            );
+
+            let mut written = Vec::new();
+            response.write_to(&mut written, None, false).await.unwrap();
+            assert_eq!(
+                without_date(&written),
+                without_date(&response.send_head(None).unwrap())
+            );
         }
     }
 }
@@ -0,0 +1,242 @@
+use std::borrow::Cow;
+
+#[derive(Clone, Debug)]
+pub struct Status {
+    code: u16,
+    reason: Cow<'static, str>,
+}
+
+macro_rules! status_codes {
+    ($($konst:ident => $code:literal, $reason:literal;)*) => {
+        impl Status {
+            $(pub const $konst: Status = Status::from_static($code, $reason);)*
+        }
+
+        fn canonical_reason(code: u16) -> Option<&'static str> {
+            match code {
+                $($code => Some($reason),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+status_codes! {
+    CONTINUE => 100, "Continue";
+    SWITCHING_PROTOCOLS => 101, "Switching Protocols";
+    PROCESSING => 102, "Processing";
+    EARLY_HINTS => 103, "Early Hints";
+
+    OK => 200, "OK";
+    CREATED => 201, "Created";
+    ACCEPTED => 202, "Accepted";
+    NON_AUTHORITATIVE_INFORMATION => 203, "Non-Authoritative Information";
+    NO_CONTENT => 204, "No Content";
+    RESET_CONTENT => 205, "Reset Content";
+    PARTIAL_CONTENT => 206, "Partial Content";
+    MULTI_STATUS => 207, "Multi-Status";
+    ALREADY_REPORTED => 208, "Already Reported";
+    IM_USED => 226, "IM Used";
+
+    MULTIPLE_CHOICES => 300, "Multiple Choices";
+    MOVED_PERMANENTLY => 301, "Moved Permanently";
+    FOUND => 302, "Found";
+    SEE_OTHER => 303, "See Other";
+    NOT_MODIFIED => 304, "Not Modified";
+    USE_PROXY => 305, "Use Proxy";
+    TEMPORARY_REDIRECT => 307, "Temporary Redirect";
+    PERMANENT_REDIRECT => 308, "Permanent Redirect";
+
+    BAD_REQUEST => 400, "Bad Request";
+    UNAUTHORIZED => 401, "Unauthorized";
+    PAYMENT_REQUIRED => 402, "Payment Required";
+    FORBIDDEN => 403, "Forbidden";
+    NOT_FOUND => 404, "Not Found";
+    METHOD_NOT_ALLOWED => 405, "Method Not Allowed";
+    NOT_ACCEPTABLE => 406, "Not Acceptable";
+    PROXY_AUTHENTICATION_REQUIRED => 407, "Proxy Authentication Required";
+    REQUEST_TIMEOUT => 408, "Request Timeout";
+    CONFLICT => 409, "Conflict";
+    GONE => 410, "Gone";
+    LENGTH_REQUIRED => 411, "Length Required";
+    PRECONDITION_FAILED => 412, "Precondition Failed";
+    CONTENT_TOO_LARGE => 413, "Content Too Large";
+    URI_TOO_LONG => 414, "URI Too Long";
+    UNSUPPORTED_MEDIA_TYPE => 415, "Unsupported Media Type";
+    RANGE_NOT_SATISFIABLE => 416, "Range Not Satisfiable";
+    EXPECTATION_FAILED => 417, "Expectation Failed";
+    MISDIRECTED_REQUEST => 421, "Misdirected Request";
+    UNPROCESSABLE_CONTENT => 422, "Unprocessable Content";
+    LOCKED => 423, "Locked";
+    FAILED_DEPENDENCY => 424, "Failed Dependency";
+    TOO_EARLY => 425, "Too Early";
+    UPGRADE_REQUIRED => 426, "Upgrade Required";
+    PRECONDITION_REQUIRED => 428, "Precondition Required";
+    TOO_MANY_REQUESTS => 429, "Too Many Requests";
+    REQUEST_HEADER_FIELDS_TOO_LARGE => 431, "Request Header Fields Too Large";
+    UNAVAILABLE_FOR_LEGAL_REASONS => 451, "Unavailable For Legal Reasons";
+
+    INTERNAL_SERVER_ERROR => 500, "Internal Server Error";
+    NOT_IMPLEMENTED => 501, "Not Implemented";
+    BAD_GATEWAY => 502, "Bad Gateway";
+    SERVICE_UNAVAILABLE => 503, "Service Unavailable";
+    GATEWAY_TIMEOUT => 504, "Gateway Timeout";
+    HTTP_VERSION_NOT_SUPPORTED => 505, "HTTP Version Not Supported";
+    VARIANT_ALSO_NEGOTIATES => 506, "Variant Also Negotiates";
+    INSUFFICIENT_STORAGE => 507, "Insufficient Storage";
+    LOOP_DETECTED => 508, "Loop Detected";
+    NOT_EXTENDED => 510, "Not Extended";
+    NETWORK_AUTHENTICATION_REQUIRED => 511, "Network Authentication Required";
+}
+
+impl Status {
+    const fn from_static(code: u16, reason: &'static str) -> Self {
+        Self {
+            code,
+            reason: Cow::Borrowed(reason),
+        }
+    }
+
+    pub fn from_u16(code: u16) -> Result<Self, InvalidStatus> {
+        if !(100..=999).contains(&code) {
+            return Err(InvalidStatus::Code(code));
+        }
+        Ok(Self::from_static(
+            code,
+            canonical_reason(code).unwrap_or_default(),
+        ))
+    }
+
+    pub fn with_reason(
+        mut self,
+        reason: impl Into<Cow<'static, str>>,
+    ) -> Result<Self, InvalidStatus> {
+        let reason = reason.into();
+        if reason
+            .bytes()
+            .any(|b| (b < b' ' && b != b'\t') || b == 0x7f)
+        {
+            return Err(InvalidStatus::Reason);
+        }
+        self.reason = reason;
+        Ok(self)
+    }
+
+    pub fn as_u16(&self) -> u16 {
+        self.code
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn canonical_reason(&self) -> Option<&'static str> {
+        canonical_reason(self.code)
+    }
+
+    pub fn is_informational(&self) -> bool {
+        (100..200).contains(&self.code)
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.code)
+    }
+
+    pub fn is_redirection(&self) -> bool {
+        (300..400).contains(&self.code)
+    }
+
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.code)
+    }
+
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.code)
+    }
+}
+
+impl PartialEq for Status {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code
+    }
+}
+
+impl Eq for Status {}
+
+impl std::hash::Hash for Status {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.code.hash(state);
+    }
+}
+
+impl TryFrom<u16> for Status {
+    type Error = InvalidStatus;
+
+    fn try_from(code: u16) -> Result<Self, InvalidStatus> {
+        Status::from_u16(code)
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.code, self.reason)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InvalidStatus {
+    Code(u16),
+    Reason,
+}
+
+impl std::fmt::Display for InvalidStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Code(code) => write!(f, "Invalid status code {}, expected 100 to 999", code),
+            Self::Reason => write!(f, "Reason phrase contains control characters"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidStatus {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_and_arbitrary_codes() {
+        assert_eq!(Status::from_u16(206), Ok(Status::PARTIAL_CONTENT));
+        assert_eq!(Status::PARTIAL_CONTENT.to_string(), "206 Partial Content");
+        assert_eq!(
+            Status::from_u16(451).unwrap().reason(),
+            "Unavailable For Legal Reasons"
+        );
+
+        let custom = Status::from_u16(299).unwrap();
+        assert_eq!(custom.reason(), "");
+        assert_eq!(custom.canonical_reason(), None);
+        assert!(custom.is_success());
+
+        let custom = custom.with_reason("Mostly Fine").unwrap();
+        assert_eq!(custom.to_string(), "299 Mostly Fine");
+        assert_eq!(Status::OK.with_reason("Fine").unwrap(), Status::OK);
+
+        assert_eq!(Status::from_u16(99), Err(InvalidStatus::Code(99)));
+        assert_eq!(Status::from_u16(1000), Err(InvalidStatus::Code(1000)));
+        assert_eq!(
+            Status::OK.with_reason("OK\r\nSet-Cookie: x=1"),
+            Err(InvalidStatus::Reason)
+        );
+    }
+
+    #[test]
+    fn classes() {
+        assert!(Status::CONTINUE.is_informational());
+        assert!(Status::NO_CONTENT.is_success());
+        assert!(Status::PERMANENT_REDIRECT.is_redirection());
+        assert!(Status::TOO_MANY_REQUESTS.is_client_error());
+        assert!(Status::NETWORK_AUTHENTICATION_REQUIRED.is_server_error());
+        assert!(!Status::OK.is_client_error());
+    }
+}
@@ -1 +1,12 @@
+pub mod de;
+pub mod extract;
+pub mod handler;
+pub mod middleware;
+pub mod openapi;
+pub mod path;
+pub mod route;
 pub mod router;
+pub use extract::{FromContext, Json, Path, Query, Rejection, State};
+pub use handler::Handler;
+pub use path::SlashPolicy;
+pub use route::{RouteHandle, RouteInfo};
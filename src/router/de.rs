@@ -0,0 +1,299 @@
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+
+use crate::router::path::PathParams;
+
+#[derive(Debug, PartialEq)]
+pub struct ParamsError(pub String);
+
+impl std::fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+impl de::Error for ParamsError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ParamsError(msg.to_string())
+    }
+}
+
+pub fn from_params<T: DeserializeOwned>(params: &PathParams) -> Result<T, ParamsError> {
+    T::deserialize(ParamsDeserializer {
+        params: params.iter().collect(),
+    })
+}
+
+struct ParamsDeserializer<'p> {
+    params: Vec<(&'p str, &'p str)>,
+}
+
+impl<'p> ParamsDeserializer<'p> {
+    fn single(self) -> Result<ValueDeserializer<'p>, ParamsError> {
+        match self.params.as_slice() {
+            [(_, value)] => Ok(ValueDeserializer(value)),
+            params => Err(ParamsError(format!(
+                "expected 1 path parameter, found {}",
+                params.len()
+            ))),
+        }
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($method:ident)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+            self.single()?.$method(visitor)
+        })*
+    };
+}
+
+impl<'de, 'p> Deserializer<'de> for ParamsDeserializer<'p> {
+    type Error = ParamsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        visitor.visit_map(ParamsAccess {
+            params: self.params.into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        visitor.visit_seq(ValuesAccess {
+            values: self.params.into_iter().map(|(_, value)| value),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        if self.params.len() != len {
+            return Err(ParamsError(format!(
+                "expected {} path parameters, found {}",
+                len,
+                self.params.len()
+            )));
+        }
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_single! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32
+        deserialize_f64 deserialize_char deserialize_str deserialize_string deserialize_bytes
+        deserialize_byte_buf deserialize_option deserialize_unit deserialize_identifier
+        deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        visitor.visit_unit()
+    }
+}
+
+struct ParamsAccess<'p, I> {
+    params: I,
+    value: Option<&'p str>,
+}
+
+impl<'de, 'p, I: Iterator<Item = (&'p str, &'p str)>> MapAccess<'de> for ParamsAccess<'p, I> {
+    type Error = ParamsError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ParamsError> {
+        match self.params.next() {
+            Some((name, value)) => {
+                self.value = Some(value);
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, ParamsError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| ParamsError("value requested before key".to_string()))?;
+        seed.deserialize(ValueDeserializer(value))
+    }
+}
+
+struct ValuesAccess<I> {
+    values: I,
+}
+
+impl<'de, 'p, I: Iterator<Item = &'p str>> SeqAccess<'de> for ValuesAccess<I> {
+    type Error = ParamsError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, ParamsError> {
+        match self.values.next() {
+            Some(value) => seed.deserialize(ValueDeserializer(value)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct ValueDeserializer<'p>(&'p str);
+
+impl ValueDeserializer<'_> {
+    fn parse<T: std::str::FromStr>(&self, kind: &str) -> Result<T, ParamsError> {
+        self.0
+            .parse()
+            .map_err(|_| ParamsError(format!("cannot parse `{}` as {}", self.0, kind)))
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident $ty:ty,)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+            visitor.$visit(self.parse::<$ty>(stringify!($ty))?)
+        })*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = ParamsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        visitor.visit_str(self.0)
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool bool,
+        deserialize_i8 => visit_i8 i8,
+        deserialize_i16 => visit_i16 i16,
+        deserialize_i32 => visit_i32 i32,
+        deserialize_i64 => visit_i64 i64,
+        deserialize_u8 => visit_u8 u8,
+        deserialize_u16 => visit_u16 u16,
+        deserialize_u32 => visit_u32 u32,
+        deserialize_u64 => visit_u64 u64,
+        deserialize_f32 => visit_f32 f32,
+        deserialize_f64 => visit_f64 f64,
+        deserialize_char => visit_char char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::path::PathPattern;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Post {
+        user: u32,
+        slug: String,
+    }
+
+    #[test]
+    fn deserializes_structs_tuples_and_single_values() {
+        let params = PathPattern::parse("/users/{user}/posts/{slug}")
+            .matches("/users/7/posts/intro")
+            .unwrap();
+        assert_eq!(
+            from_params::<Post>(&params),
+            Ok(Post {
+                user: 7,
+                slug: "intro".to_string()
+            })
+        );
+        assert_eq!(
+            from_params::<(u32, String)>(&params),
+            Ok((7, "intro".to_string()))
+        );
+        assert!(from_params::<u32>(&params).is_err());
+
+        let params = PathPattern::parse("/users/{id}")
+            .matches("/users/abc")
+            .unwrap();
+        assert_eq!(from_params::<String>(&params), Ok("abc".to_string()));
+        assert_eq!(
+            from_params::<u32>(&params),
+            Err(ParamsError("cannot parse `abc` as u32".to_string()))
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::Arc;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    headers::{Headers, typed::ContentType},
+    request::Body,
+    response::{IntoResponse, Response, Status},
+    router::{de::from_params, path::PathParams, router::Context},
+};
+
+pub trait FromContext: Sized {
+    type Rejection: IntoResponse;
+
+    fn from_context(ctx: &mut Context) -> Result<Self, Self::Rejection>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Rejection {
+    InvalidPath(String),
+    InvalidQuery(String),
+    UnsupportedMediaType,
+    MalformedJson(String),
+    InvalidJson(String),
+    MissingState(&'static str),
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPath(e) => write!(f, "Invalid path parameters: {}", e),
+            Self::InvalidQuery(e) => write!(f, "Invalid query string: {}", e),
+            Self::UnsupportedMediaType => write!(f, "Expected an application/json body"),
+            Self::MalformedJson(e) => write!(f, "Malformed JSON body: {}", e),
+            Self::InvalidJson(e) => write!(f, "Invalid JSON body: {}", e),
+            Self::MissingState(name) => write!(f, "No state of type {} registered", name),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::InvalidPath(_) => Status::NOT_FOUND,
+            Self::InvalidQuery(_) | Self::MalformedJson(_) => Status::BAD_REQUEST,
+            Self::UnsupportedMediaType => Status::UNSUPPORTED_MEDIA_TYPE,
+            Self::InvalidJson(_) => Status::UNPROCESSABLE_CONTENT,
+            Self::MissingState(_) => return Status::INTERNAL_SERVER_ERROR.into_response(),
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path<T>(pub T);
+
+impl<T: DeserializeOwned> FromContext for Path<T> {
+    type Rejection = Rejection;
+
+    fn from_context(ctx: &mut Context) -> Result<Self, Rejection> {
+        let params = ctx.get::<PathParams>().cloned().unwrap_or_default();
+        from_params(&params)
+            .map(Path)
+            .map_err(|e| Rejection::InvalidPath(e.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query<T>(pub T);
+
+impl<T: DeserializeOwned> FromContext for Query<T> {
+    type Rejection = Rejection;
+
+    fn from_context(ctx: &mut Context) -> Result<Self, Rejection> {
+        let query = ctx
+            .request
+            .request_line
+            .request_target
+            .split_once('?')
+            .map_or("", |(_, query)| query);
+        serde_urlencoded::from_str(query)
+            .map(Query)
+            .map_err(|e| Rejection::InvalidQuery(e.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Json<T>(pub T);
+
+impl<T: DeserializeOwned> FromContext for Json<T> {
+    type Rejection = Rejection;
+
+    fn from_context(ctx: &mut Context) -> Result<Self, Rejection> {
+        let is_json = ctx
+            .request
+            .headers
+            .typed_get::<ContentType>()
+            .ok()
+            .flatten()
+            .is_some_and(|ContentType(media_type)| {
+                media_type.kind == "application"
+                    && (media_type.subtype == "json" || media_type.subtype.ends_with("+json"))
+            });
+        if !is_json {
+            return Err(Rejection::UnsupportedMediaType);
+        }
+
+        let body = ctx.request.body.take();
+        serde_json::from_slice(body.as_ref().map_or(&[], |body| body.as_bytes()))
+            .map(Json)
+            .map_err(|e| match e.classify() {
+                serde_json::error::Category::Data => Rejection::InvalidJson(e.to_string()),
+                _ => Rejection::MalformedJson(e.to_string()),
+            })
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        match serde_json::to_vec(&self.0) {
+            Ok(body) => {
+                let mut response = body.into_response();
+                response
+                    .set_header("Content-Type", "application/json")
+                    .unwrap();
+                response
+            }
+            Err(_) => Status::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
+pub struct State<S>(pub Arc<S>);
+
+impl<S: Send + Sync + 'static> FromContext for State<S> {
+    type Rejection = Rejection;
+
+    fn from_context(ctx: &mut Context) -> Result<Self, Rejection> {
+        ctx.state::<S>()
+            .map(State)
+            .ok_or(Rejection::MissingState(std::any::type_name::<S>()))
+    }
+}
+
+impl FromContext for Headers {
+    type Rejection = Rejection;
+
+    fn from_context(ctx: &mut Context) -> Result<Self, Rejection> {
+        Ok(ctx.request.headers.clone())
+    }
+}
+
+impl FromContext for Body {
+    type Rejection = Rejection;
+
+    fn from_context(ctx: &mut Context) -> Result<Self, Rejection> {
+        Ok(ctx
+            .request
+            .body
+            .take()
+            .unwrap_or_else(|| Body::new(Vec::new())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        request::{request::Request, request_from_reader},
+        router::router::Router,
+    };
+    use serde::Deserialize;
+
+    struct Greeting(String);
+
+    #[derive(Deserialize)]
+    struct Page {
+        page: u32,
+        tag: Option<String>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct Order {
+        coffee: String,
+        shots: u8,
+    }
+
+    async fn greet(Path(name): Path<String>, State(greeting): State<Greeting>) -> String {
+        format!("{} {}", greeting.0, name)
+    }
+
+    async fn list(Path((user, post)): Path<(u32, u32)>, Query(page): Query<Page>) -> String {
+        format!("{user}/{post} page {} {:?}", page.page, page.tag)
+    }
+
+    async fn order(headers: Headers, Json(order): Json<Order>) -> (Status, Json<Order>) {
+        let shots = order.shots + u8::from(headers.contains("x-double"));
+        (Status::CREATED, Json(Order { shots, ..order }))
+    }
+
+    fn router() -> Router {
+        let mut router = Router::new();
+        router.add_state(Greeting("Hello".to_string()));
+        router.get("/greet/{name}", greet);
+        router.get("/users/{user}/posts/{post}", list);
+        router.post("/orders", order);
+        router.post(
+            "/echo",
+            |body: Body| async move { body.as_bytes().to_vec() },
+        );
+        router
+    }
+
+    async fn request(method: &str, target: &str, headers: &str, body: &str) -> Request {
+        let raw = format!(
+            "{method} {target} HTTP/1.1\r\nContent-Length: {}\r\n{headers}\r\n{body}",
+            body.len()
+        );
+        request_from_reader(raw.as_bytes()).await.unwrap()
+    }
+
+    async fn send(router: &Router, request: Request) -> Response {
+        router.handle_request(request, Response::new()).await
+    }
+
+    #[tokio::test]
+    async fn extracts_path_query_and_state() {
+        let router = router();
+        let response = send(&router, request("GET", "/greet/caf%C3%A9", "", "").await).await;
+        assert_eq!(response.body_bytes(), "Hello café".as_bytes());
+
+        let response = send(
+            &router,
+            request("GET", "/users/3/posts/9?page=2&tag=rust", "", "").await,
+        )
+        .await;
+        assert_eq!(response.body_bytes(), b"3/9 page 2 Some(\"rust\")");
+
+        let response = send(&router, request("GET", "/users/3/posts/x", "", "").await).await;
+        assert_eq!(response.status_code(), 404);
+        let response = send(
+            &router,
+            request("GET", "/users/3/posts/9?page=a", "", "").await,
+        )
+        .await;
+        assert_eq!(response.status_code(), 400);
+    }
+
+    #[tokio::test]
+    async fn extracts_json_bodies() {
+        let router = router();
+        let json = "Content-Type: application/json\r\nX-Double: 1\r\n";
+        let response = send(
+            &router,
+            request(
+                "POST",
+                "/orders",
+                json,
+                r#"{"coffee":"flat white","shots":1}"#,
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(response.status_code(), 201);
+        assert_eq!(
+            response.headers().get("content-type"),
+            Some("application/json")
+        );
+        assert_eq!(
+            response.body_bytes(),
+            br#"{"coffee":"flat white","shots":2}"#
+        );
+
+        let cases = [
+            ("", r#"{"coffee":"latte","shots":1}"#, 415),
+            (json, r#"{"coffee":"latte""#, 400),
+            (json, r#"{"coffee":"latte","shots":"one"}"#, 422),
+        ];
+        for (headers, body, status) in cases {
+            let response = send(&router, request("POST", "/orders", headers, body).await).await;
+            assert_eq!(response.status_code(), status, "{body}");
+        }
+
+        let response = send(&router, request("POST", "/echo", "", "raw bytes").await).await;
+        assert_eq!(response.body_bytes(), b"raw bytes");
+    }
+}
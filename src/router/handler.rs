@@ -0,0 +1,60 @@
+use std::future::Future;
+
+use crate::{
+    response::{IntoResponse, Response},
+    router::{
+        extract::FromContext,
+        middleware::BoxFuture,
+        router::{Context, HandlerResult},
+    },
+};
+
+pub trait Handler<Args>: Send + Sync + 'static {
+    fn call(&self, ctx: Context) -> BoxFuture<'static, Response>;
+}
+
+impl<F, Fut> Handler<Context> for F
+where
+    F: Fn(Context) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = HandlerResult> + Send + 'static,
+{
+    fn call(&self, ctx: Context) -> BoxFuture<'static, Response> {
+        let future = self(ctx);
+        Box::pin(async move { future.await.into_response() })
+    }
+}
+
+macro_rules! extractor_handler {
+    ($($extractor:ident),*) => {
+        impl<F, Fut, $($extractor,)*> Handler<($($extractor,)*)> for F
+        where
+            F: Fn($($extractor),*) -> Fut + Send + Sync + 'static,
+            Fut: Future + Send + 'static,
+            Fut::Output: IntoResponse,
+            $($extractor: FromContext,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn call(&self, mut ctx: Context) -> BoxFuture<'static, Response> {
+                $(
+                    let $extractor = match $extractor::from_context(&mut ctx) {
+                        Ok(value) => value,
+                        Err(rejection) => {
+                            let response = rejection.into_response();
+                            return Box::pin(async move { response });
+                        }
+                    };
+                )*
+                let future = self($($extractor),*);
+                Box::pin(async move { future.await.into_response() })
+            }
+        }
+    };
+}
+
+extractor_handler!();
+extractor_handler!(T1);
+extractor_handler!(T1, T2);
+extractor_handler!(T1, T2, T3);
+extractor_handler!(T1, T2, T3, T4);
+extractor_handler!(T1, T2, T3, T4, T5);
+extractor_handler!(T1, T2, T3, T4, T5, T6);
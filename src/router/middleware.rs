@@ -0,0 +1,58 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use crate::{
+    response::Response,
+    router::router::{AsyncHandler, Context, Router},
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+pub trait Middleware: Send + Sync {
+    fn handle<'a>(&'a self, ctx: Context, next: Next<'a>) -> BoxFuture<'a, Response>;
+}
+
+#[derive(Clone, Copy)]
+enum Endpoint<'a> {
+    Router(&'a Router),
+    Handler(&'a AsyncHandler),
+}
+
+pub struct Next<'a> {
+    endpoint: Endpoint<'a>,
+    middlewares: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(router: &'a Router, middlewares: &'a [Arc<dyn Middleware>]) -> Self {
+        Self {
+            endpoint: Endpoint::Router(router),
+            middlewares,
+        }
+    }
+
+    pub(crate) fn handler(
+        handler: &'a AsyncHandler,
+        middlewares: &'a [Arc<dyn Middleware>],
+    ) -> Self {
+        Self {
+            endpoint: Endpoint::Handler(handler),
+            middlewares,
+        }
+    }
+
+    pub fn run(self, ctx: Context) -> BoxFuture<'a, Response> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => middleware.handle(
+                ctx,
+                Next {
+                    endpoint: self.endpoint,
+                    middlewares: rest,
+                },
+            ),
+            None => match self.endpoint {
+                Endpoint::Router(router) => Box::pin(router.dispatch(ctx)),
+                Endpoint::Handler(handler) => handler(ctx),
+            },
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::sync::OnceLock;
+
+use schemars::generate::SchemaSettings;
+use serde_json::{Map, Value, json};
+
+use crate::router::route::Route;
+
+pub(crate) struct OpenApiEndpoint {
+    pub(crate) path: String,
+    pub(crate) title: String,
+    pub(crate) version: String,
+    pub(crate) document: OnceLock<Vec<u8>>,
+}
+
+pub(crate) fn document(routes: &[Route], title: &str, version: &str) -> Value {
+    let mut generator = SchemaSettings::draft2020_12()
+        .with(|settings| {
+            settings.definitions_path = "/components/schemas".into();
+            settings.meta_schema = None;
+        })
+        .into_generator();
+
+    let mut paths = Map::new();
+    for route in routes.iter() {
+        let Some(method) = route.method.as_deref() else {
+            continue;
+        };
+
+        let mut operation = Map::new();
+        operation.insert("operationId".to_string(), json!(operation_id(route)));
+        if let Some(summary) = &route.docs.summary {
+            operation.insert("summary".to_string(), json!(summary));
+        }
+        if !route.docs.tags.is_empty() {
+            operation.insert("tags".to_string(), json!(route.docs.tags));
+        }
+
+        let parameters: Vec<Value> = route
+            .pattern
+            .params()
+            .map(|name| {
+                let schema = match route.docs.params.iter().find(|(param, _)| param == name) {
+                    Some((_, schema)) => schema(&mut generator).to_value(),
+                    None => json!({ "type": "string" }),
+                };
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": schema,
+                })
+            })
+            .collect();
+        if !parameters.is_empty() {
+            operation.insert("parameters".to_string(), Value::Array(parameters));
+        }
+
+        if let Some(schema) = route.docs.request {
+            operation.insert(
+                "requestBody".to_string(),
+                json!({
+                    "required": true,
+                    "content": { "application/json": { "schema": schema(&mut generator) } },
+                }),
+            );
+        }
+
+        let mut responses = Map::new();
+        for (status, schema) in route.docs.responses.iter() {
+            let mut response = Map::new();
+            response.insert("description".to_string(), json!(status.reason()));
+            if let Some(schema) = schema {
+                response.insert(
+                    "content".to_string(),
+                    json!({ "application/json": { "schema": schema(&mut generator) } }),
+                );
+            }
+            responses.insert(status.as_u16().to_string(), Value::Object(response));
+        }
+        if !responses.is_empty() {
+            operation.insert("responses".to_string(), Value::Object(responses));
+        }
+
+        paths
+            .entry(route.pattern.as_str())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .unwrap()
+            .insert(method.to_ascii_lowercase(), Value::Object(operation));
+    }
+
+    let mut document = json!({
+        "openapi": "3.1.0",
+        "info": { "title": title, "version": version },
+        "paths": paths,
+    });
+    let schemas = generator.take_definitions(true);
+    if !schemas.is_empty() {
+        document["components"] = json!({ "schemas": schemas });
+    }
+    document
+}
+
+fn operation_id(route: &Route) -> String {
+    let method = route
+        .method
+        .as_deref()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let path: Vec<String> = route
+        .pattern
+        .as_str()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            segment
+                .trim_matches(|c| c == '{' || c == '}')
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+        })
+        .collect();
+    if path.is_empty() {
+        method
+    } else {
+        format!("{}_{}", method, path.join("_"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::request_from_reader,
+        response::{Response, Status},
+        router::{Json, Path, router::Router},
+    };
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{Value, json};
+
+    #[derive(Deserialize, JsonSchema)]
+    struct NewUser {
+        name: String,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    struct User {
+        id: u32,
+        name: String,
+    }
+
+    fn router() -> Router {
+        let mut router = Router::new();
+        router
+            .post("/users", |Json(user): Json<NewUser>| async move {
+                (
+                    Status::CREATED,
+                    Json(User {
+                        id: 1,
+                        name: user.name,
+                    }),
+                )
+            })
+            .summary("Create a user")
+            .tag("users")
+            .request::<NewUser>()
+            .response::<User>(Status::CREATED)
+            .empty_response(Status::UNPROCESSABLE_CONTENT);
+        router
+            .get("/users/{id}", |Path(id): Path<u32>| async move {
+                Json(User {
+                    id,
+                    name: "alice".to_string(),
+                })
+            })
+            .param::<u32>("id")
+            .response::<User>(Status::OK);
+        router.get("/users/{id}/posts/{slug}", || async { "post" });
+        router.add_route("/health", || async { "ok" });
+        router.serve_openapi("/openapi.json", "Users", "1.0.0");
+        router
+    }
+
+    #[tokio::test]
+    async fn serves_openapi_document() {
+        let router = router();
+        let raw = "GET /openapi.json HTTP/1.1\r\n\r\n";
+        let request = request_from_reader(raw.as_bytes()).await.unwrap();
+        let response = router.handle_request(request, Response::new()).await;
+        assert_eq!(
+            response.headers().get("content-type"),
+            Some("application/json")
+        );
+        let document: Value = serde_json::from_slice(response.body_bytes()).unwrap();
+        assert_eq!(document, router.openapi("Users", "1.0.0"));
+
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(document["info"]["title"], "Users");
+        let create = &document["paths"]["/users"]["post"];
+        assert_eq!(create["operationId"], "post_users");
+        assert_eq!(create["summary"], "Create a user");
+        assert_eq!(create["tags"][0], "users");
+        assert_eq!(
+            create["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/NewUser"
+        );
+        assert_eq!(
+            create["responses"]["201"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/User"
+        );
+        assert_eq!(
+            create["responses"]["422"]["description"],
+            "Unprocessable Content"
+        );
+
+        let show = &document["paths"]["/users/{id}"]["get"];
+        assert_eq!(show["parameters"][0]["name"], "id");
+        assert_eq!(show["parameters"][0]["in"], "path");
+        assert_eq!(show["parameters"][0]["schema"]["type"], "integer");
+        let post = &document["paths"]["/users/{id}/posts/{slug}"]["get"];
+        assert_eq!(post["parameters"][1]["name"], "slug");
+        assert_eq!(post["parameters"][1]["schema"], json!({ "type": "string" }));
+        assert_eq!(
+            document["components"]["schemas"]["User"]["properties"]["id"]["type"],
+            "integer"
+        );
+        assert!(document["paths"].get("/health").is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Route `GET /users/{id}` has no parameter `name`")]
+    fn rejects_unknown_param_schema() {
+        let mut router = Router::new();
+        router
+            .get("/users/{id}", || async { "user" })
+            .param::<String>("name");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Route `GET /{file}` conflicts with the OpenAPI document at `/openapi.json`"
+    )]
+    fn rejects_route_shadowed_by_openapi() {
+        let mut router = Router::new();
+        router.serve_openapi("/openapi.json", "Files", "1.0.0");
+        router.get("/{file}", || async { "file" });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Route `* /api/openapi.json` conflicts with the OpenAPI document at `/api/openapi.json`"
+    )]
+    fn rejects_openapi_over_existing_route() {
+        let mut api = Router::new();
+        api.serve_openapi("/openapi.json", "Api", "1.0.0");
+        let mut router = Router::new();
+        router.add_route("/api/openapi.json", || async { "static" });
+        router.nest("/api", api);
+    }
+}
@@ -0,0 +1,309 @@
+use std::{borrow::Cow, cmp::Ordering};
+
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Static(String),
+    Param(String),
+}
+
+impl Segment {
+    fn rank(&self) -> (u8, &str) {
+        match self {
+            Segment::Static(segment) => (0, segment),
+            Segment::Param(_) => (1, ""),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathPattern {
+    pattern: String,
+    segments: Vec<Segment>,
+}
+
+impl PathPattern {
+    pub fn parse(pattern: &str) -> Self {
+        let trailing = pattern.ends_with('/') && split(pattern).next().is_some();
+        let segments = split(pattern)
+            .map(|segment| {
+                match segment
+                    .strip_prefix('{')
+                    .and_then(|segment| segment.strip_suffix('}'))
+                {
+                    Some(name) => Segment::Param(name.to_string()),
+                    None => Segment::Static(segment.to_string()),
+                }
+            })
+            .chain(trailing.then(|| Segment::Static(String::new())))
+            .collect();
+        Self {
+            pattern: pattern.to_string(),
+            segments,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Param(name) => Some(name.as_str()),
+            Segment::Static(_) => None,
+        })
+    }
+
+    pub fn prefixed(&self, prefix: &str) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+        match self.pattern.as_str() {
+            "" | "/" if !prefix.is_empty() => PathPattern::parse(prefix),
+            pattern => {
+                PathPattern::parse(&format!("{}/{}", prefix, pattern.trim_start_matches('/')))
+            }
+        }
+    }
+
+    pub fn conflicts_with(&self, other: &PathPattern) -> bool {
+        self.segments.len() == other.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(other.segments.iter())
+                .all(|pair| match pair {
+                    (Segment::Static(a), Segment::Static(b)) => a == b,
+                    (Segment::Param(_), Segment::Param(_)) => true,
+                    _ => false,
+                })
+    }
+
+    pub fn specificity(&self, other: &PathPattern) -> Ordering {
+        self.segments
+            .iter()
+            .map(Segment::rank)
+            .cmp(other.segments.iter().map(Segment::rank))
+    }
+
+    pub fn render(&self, params: &[(&str, &str)]) -> Result<String, UrlError> {
+        let mut path = String::new();
+        for segment in self.segments.iter() {
+            path.push('/');
+            match segment {
+                Segment::Static(segment) => path.push_str(segment),
+                Segment::Param(name) => {
+                    let (_, value) = params
+                        .iter()
+                        .find(|(param, _)| param == name)
+                        .ok_or_else(|| UrlError::MissingParam(name.clone()))?;
+                    path.extend(utf8_percent_encode(value, SEGMENT));
+                }
+            }
+        }
+        if path.is_empty() {
+            path.push('/');
+        }
+        Ok(path)
+    }
+
+    pub fn matches(&self, path: &str) -> Option<PathParams> {
+        let mut params = PathParams::default();
+        let rest = path.strip_prefix('/')?;
+        let mut parts = (!rest.is_empty())
+            .then(|| rest.split('/'))
+            .into_iter()
+            .flatten();
+        for segment in self.segments.iter() {
+            let part = parts.next()?;
+            match segment {
+                Segment::Static(expected) if expected == part => {}
+                Segment::Static(_) => return None,
+                Segment::Param(_) if part.is_empty() => return None,
+                Segment::Param(name) => {
+                    let value = percent_decode_str(part).decode_utf8().ok()?;
+                    params.0.push((name.clone(), value.into_owned()));
+                }
+            }
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(params)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PathParams(Vec<(String, String)>);
+
+impl PathParams {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SlashPolicy {
+    #[default]
+    Strict,
+    Redirect,
+    Normalize,
+}
+
+pub fn normalize(path: &str) -> Cow<'_, str> {
+    let is_normal =
+        path.starts_with('/') && !path.contains("//") && (path == "/" || !path.ends_with('/'));
+    if is_normal {
+        return Cow::Borrowed(path);
+    }
+    let mut normalized = String::with_capacity(path.len());
+    for segment in split(path) {
+        normalized.push('/');
+        normalized.push_str(segment);
+    }
+    if normalized.is_empty() {
+        normalized.push('/');
+    }
+    Cow::Owned(normalized)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UrlError {
+    UnknownRoute(String),
+    MissingParam(String),
+}
+
+impl std::fmt::Display for UrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownRoute(name) => write!(f, "No route named `{}`", name),
+            Self::MissingParam(name) => write!(f, "Missing path parameter `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for UrlError {}
+
+fn split(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_static_and_param_segments() {
+        let pattern = PathPattern::parse("/users/{id}/posts/{slug}");
+        assert_eq!(pattern.params().collect::<Vec<_>>(), ["id", "slug"]);
+
+        let params = pattern.matches("/users/42/posts/hello%20world").unwrap();
+        assert_eq!(params.get("id"), Some("42"));
+        assert_eq!(params.get("slug"), Some("hello world"));
+
+        assert!(pattern.matches("/users/42/posts").is_none());
+        assert!(pattern.matches("/users/42/comments/1").is_none());
+        assert!(pattern.matches("/users/42/posts/1/extra").is_none());
+        assert!(pattern.matches("/users/42/posts/1/").is_none());
+        assert!(pattern.matches("/users//posts/1").is_none());
+        assert!(pattern.matches("//users/42/posts/1").is_none());
+        assert!(PathPattern::parse("/").matches("/").unwrap().is_empty());
+
+        let trailing = PathPattern::parse("/users/");
+        assert!(trailing.matches("/users/").is_some());
+        assert!(trailing.matches("/users").is_none());
+        assert!(!trailing.conflicts_with(&PathPattern::parse("/users")));
+        assert_eq!(trailing.render(&[]).unwrap(), "/users/");
+    }
+
+    #[test]
+    fn detects_conflicts_and_orders_static_segments_first() {
+        let by_id = PathPattern::parse("/users/{id}");
+        assert!(by_id.conflicts_with(&PathPattern::parse("/users/{name}")));
+        assert!(!by_id.conflicts_with(&PathPattern::parse("/users/me")));
+        assert!(!by_id.conflicts_with(&PathPattern::parse("/users/{id}/posts")));
+
+        assert_eq!(
+            PathPattern::parse("/users/me").specificity(&by_id),
+            Ordering::Less
+        );
+        assert_eq!(
+            PathPattern::parse("/{a}/b").specificity(&PathPattern::parse("/a/{b}")),
+            Ordering::Greater
+        );
+
+        let patterns = [
+            "/{a}",
+            "/users/{id}",
+            "/users",
+            "/users/me",
+            "/{a}/b",
+            "/a/{b}",
+        ]
+        .map(PathPattern::parse);
+        for a in patterns.iter() {
+            assert_eq!(a.specificity(a), Ordering::Equal);
+            for b in patterns.iter() {
+                assert_eq!(a.specificity(b), b.specificity(a).reverse());
+                for c in patterns.iter() {
+                    if a.specificity(b).is_le() && b.specificity(c).is_le() {
+                        assert!(a.specificity(c).is_le());
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            PathPattern::parse("/users/{id}").specificity(&PathPattern::parse("/users/{name}")),
+            Ordering::Equal
+        );
+
+        assert_eq!(by_id.prefixed("/api/v1/").as_str(), "/api/v1/users/{id}");
+        assert_eq!(PathPattern::parse("/").prefixed("/api").as_str(), "/api");
+        assert_eq!(by_id.prefixed("").as_str(), "/users/{id}");
+    }
+
+    #[test]
+    fn normalizes_slashes() {
+        assert!(matches!(normalize("/users/42"), Cow::Borrowed("/users/42")));
+        assert!(matches!(normalize("/"), Cow::Borrowed("/")));
+        assert_eq!(normalize("/users//42/"), "/users/42");
+        assert_eq!(normalize("//"), "/");
+        assert_eq!(normalize("users/"), "/users");
+    }
+
+    #[test]
+    fn renders_encoded_params() {
+        let pattern = PathPattern::parse("/files/{dir}/{name}");
+        assert_eq!(
+            pattern.render(&[("name", "a b/c?.txt"), ("dir", "café")]),
+            Ok("/files/caf%C3%A9/a%20b%2Fc%3F.txt".to_string())
+        );
+        assert_eq!(
+            pattern.render(&[("dir", "docs")]),
+            Err(UrlError::MissingParam("name".to_string()))
+        );
+        assert_eq!(PathPattern::parse("/").render(&[]), Ok("/".to_string()));
+    }
+}
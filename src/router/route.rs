@@ -0,0 +1,164 @@
+use std::{collections::HashMap, sync::Arc};
+
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+
+use crate::{
+    response::Status,
+    router::{middleware::Middleware, path::PathPattern, router::AsyncHandler},
+};
+
+pub(crate) type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+#[derive(Default)]
+pub(crate) struct RouteDocs {
+    pub(crate) summary: Option<String>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) params: Vec<(String, SchemaFn)>,
+    pub(crate) request: Option<SchemaFn>,
+    pub(crate) responses: Vec<(Status, Option<SchemaFn>)>,
+}
+
+pub(crate) struct Route {
+    pub(crate) method: Option<String>,
+    pub(crate) pattern: PathPattern,
+    pub(crate) handler: AsyncHandler,
+    pub(crate) handler_name: &'static str,
+    pub(crate) middlewares: Vec<Arc<dyn Middleware>>,
+    pub(crate) docs: RouteDocs,
+}
+
+impl Route {
+    pub(crate) fn accepts(&self, method: &str) -> bool {
+        match self.method.as_deref() {
+            None => true,
+            Some(expected) => expected == method || (method == "HEAD" && expected == "GET"),
+        }
+    }
+
+    pub(crate) fn conflicts_with(&self, other: &Route) -> bool {
+        let methods_overlap = match (&self.method, &other.method) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        methods_overlap && self.pattern.conflicts_with(&other.pattern)
+    }
+
+    pub(crate) fn info(&self) -> RouteInfo<'_> {
+        RouteInfo {
+            method: self.method.as_deref(),
+            path: self.pattern.as_str(),
+            params: self.pattern.params().collect(),
+            handler: self.handler_name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteInfo<'r> {
+    pub method: Option<&'r str>,
+    pub path: &'r str,
+    pub params: Vec<&'r str>,
+    pub handler: &'static str,
+}
+
+impl std::fmt::Display for RouteInfo<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method.unwrap_or("*"), self.path)
+    }
+}
+
+pub(crate) type RouteNames = Arc<HashMap<String, PathPattern>>;
+
+pub(crate) fn add_name(names: &mut RouteNames, name: &str, pattern: &PathPattern) {
+    if let Some(existing) = names.get(name) {
+        panic!(
+            "Route name `{}` is already used by `{}`",
+            name,
+            existing.as_str()
+        );
+    }
+    Arc::make_mut(names).insert(name.to_string(), pattern.clone());
+}
+
+pub struct RouteHandle<'r> {
+    route: &'r mut Route,
+    names: &'r mut RouteNames,
+}
+
+impl<'r> RouteHandle<'r> {
+    pub(crate) fn new(route: &'r mut Route, names: &'r mut RouteNames) -> Self {
+        Self { route, names }
+    }
+
+    pub fn name(self, name: &str) -> Self {
+        add_name(self.names, name, &self.route.pattern);
+        self
+    }
+
+    pub fn summary(self, summary: &str) -> Self {
+        self.route.docs.summary = Some(summary.to_string());
+        self
+    }
+
+    pub fn tag(self, tag: &str) -> Self {
+        self.route.docs.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn param<T: JsonSchema>(self, name: &str) -> Self {
+        if !self.route.pattern.params().any(|param| param == name) {
+            panic!("Route `{}` has no parameter `{}`", self.route.info(), name);
+        }
+        self.route
+            .docs
+            .params
+            .push((name.to_string(), |generator| generator.subschema_for::<T>()));
+        self
+    }
+
+    pub fn request<T: JsonSchema>(self) -> Self {
+        self.route.docs.request = Some(|generator| generator.subschema_for::<T>());
+        self
+    }
+
+    pub fn response<T: JsonSchema>(self, status: Status) -> Self {
+        self.route
+            .docs
+            .responses
+            .push((status, Some(|generator| generator.subschema_for::<T>())));
+        self
+    }
+
+    pub fn empty_response(self, status: Status) -> Self {
+        self.route.docs.responses.push((status, None));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::router::router::{Context, HandlerResult, Router};
+
+    async fn show_user(ctx: Context) -> HandlerResult {
+        Ok(ctx.response)
+    }
+
+    #[test]
+    fn lists_registered_routes() {
+        let mut router = Router::new();
+        router.get("/users/{id}/posts/{post}", show_user);
+        router.get("/users/me", show_user);
+        router.add_route("/health", || async { "ok" });
+
+        let routes: Vec<String> = router.routes().map(|route| route.to_string()).collect();
+        assert_eq!(
+            routes,
+            ["* /health", "GET /users/me", "GET /users/{id}/posts/{post}"]
+        );
+
+        let route = router.routes().nth(2).unwrap();
+        assert_eq!(route.method, Some("GET"));
+        assert_eq!(route.params, ["id", "post"]);
+        assert_eq!(route.handler, "http::router::route::tests::show_user");
+    }
+}
@@ -1,84 +1,776 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::future::Future;
-use std::pin::Pin;
-use tokio::io::{AsyncWriteExt, WriteHalf};
-use tokio::net::TcpStream;
+use std::sync::{Arc, OnceLock};
 
+use serde_json::Value;
+
+use crate::cookie::{Cookie, CookieError, CookieKeys};
+use crate::extensions::Extensions;
+use crate::headers::InvalidHeader;
+use crate::headers::headers::HeadersError;
 use crate::request::request::Request;
-use crate::response::Response;
-use crate::response::response::Status;
+use crate::response::Status;
+use crate::response::{IntoResponse, Response};
+use crate::router::handler::Handler;
+use crate::router::middleware::{BoxFuture, Middleware, Next};
+use crate::router::openapi::{self, OpenApiEndpoint};
+use crate::router::path::{PathPattern, SlashPolicy, UrlError, normalize};
+use crate::router::route::{Route, RouteDocs, RouteHandle, RouteInfo, RouteNames, add_name};
+use crate::session::{Session, SessionError};
 
 pub type HandlerResult = Result<Response, HandlerError>;
-pub type AsyncHandler =
-    Box<dyn Fn(Context) -> Pin<Box<dyn Future<Output = HandlerResult> + Send>> + Send + Sync>;
+pub type AsyncHandler = Box<dyn Fn(Context) -> BoxFuture<'static, Response> + Send + Sync>;
 
 pub struct Router {
-    routes: HashMap<String, AsyncHandler>,
+    routes: Vec<Route>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    cookie_keys: Option<Arc<CookieKeys>>,
+    state: Arc<Extensions>,
+    names: RouteNames,
+    openapi: Option<OpenApiEndpoint>,
+    slash_policy: SlashPolicy,
 }
 
 pub struct Context {
     pub request: Request,
     pub response: Response,
+    cookie_keys: Option<Arc<CookieKeys>>,
+    state: Arc<Extensions>,
+    names: RouteNames,
+}
+
+impl Context {
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.request.extensions.insert(value)
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.request.extensions.get::<T>()
+    }
+
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.request.extensions.get_mut::<T>()
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.request.extensions.remove::<T>()
+    }
+
+    pub fn state<S: Send + Sync + 'static>(&self) -> Option<Arc<S>> {
+        self.state.get::<Arc<S>>().cloned()
+    }
+
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, UrlError> {
+        url_for(&self.names, name, params)
+    }
+
+    pub fn session(&self) -> Option<&Session> {
+        self.get::<Session>()
+    }
+
+    pub fn signed_cookie(&self, name: &str) -> Option<String> {
+        let keys = self.cookie_keys.as_ref()?;
+        let cookies = self.request.cookies();
+        keys.verify(name, cookies.get(name)?)
+    }
+
+    pub fn private_cookie(&self, name: &str) -> Option<String> {
+        let keys = self.cookie_keys.as_ref()?;
+        let cookies = self.request.cookies();
+        keys.decrypt(name, cookies.get(name)?)
+    }
+
+    pub fn add_signed_cookie(&mut self, mut cookie: Cookie) -> Result<(), CookieError> {
+        let keys = self.cookie_keys.as_ref().ok_or(CookieError::MissingKey)?;
+        cookie.value = keys.sign(&cookie.name, &cookie.value);
+        self.response.add_cookie(&cookie)
+    }
+
+    pub fn add_private_cookie(&mut self, mut cookie: Cookie) -> Result<(), CookieError> {
+        let keys = self.cookie_keys.as_ref().ok_or(CookieError::MissingKey)?;
+        cookie.value = keys.encrypt(&cookie.name, &cookie.value)?;
+        self.response.add_cookie(&cookie)
+    }
 }
 
 #[derive(Debug)]
 pub enum HandlerError {
+    BadRequest,
     NotFound,
     InternalError,
 }
 
+impl From<InvalidHeader> for HandlerError {
+    fn from(_: InvalidHeader) -> Self {
+        HandlerError::BadRequest
+    }
+}
+
+impl From<HeadersError> for HandlerError {
+    fn from(_: HeadersError) -> Self {
+        HandlerError::InternalError
+    }
+}
+
+impl From<CookieError> for HandlerError {
+    fn from(_: CookieError) -> Self {
+        HandlerError::InternalError
+    }
+}
+
+impl From<SessionError> for HandlerError {
+    fn from(_: SessionError) -> Self {
+        HandlerError::InternalError
+    }
+}
+
+impl IntoResponse for HandlerError {
+    fn into_response(self) -> Response {
+        match self {
+            HandlerError::BadRequest => Status::BAD_REQUEST,
+            HandlerError::NotFound => Status::NOT_FOUND,
+            HandlerError::InternalError => Status::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
 impl Router {
     pub fn new() -> Self {
         Self {
-            routes: HashMap::new(),
+            routes: Vec::new(),
+            middlewares: Vec::new(),
+            cookie_keys: None,
+            state: Arc::new(Extensions::new()),
+            names: Arc::new(HashMap::new()),
+            openapi: None,
+            slash_policy: SlashPolicy::Strict,
         }
     }
 
-    pub fn add_route<F, Fut>(&mut self, path: &str, handler: F)
+    pub fn add_state<S: Send + Sync + 'static>(&mut self, state: S) {
+        state_mut(&mut self.state).insert(Arc::new(state));
+    }
+
+    pub fn add_middleware(&mut self, middleware: impl Middleware + 'static) {
+        self.middlewares.push(Arc::new(middleware));
+    }
+
+    pub fn set_cookie_keys(&mut self, keys: CookieKeys) {
+        self.cookie_keys = Some(Arc::new(keys));
+    }
+
+    pub fn set_slash_policy(&mut self, policy: SlashPolicy) {
+        self.slash_policy = policy;
+    }
+
+    pub fn add_route<H, Args>(&mut self, path: &str, handler: H) -> RouteHandle<'_>
     where
-        F: Fn(Context) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = HandlerResult> + Send + 'static,
+        H: Handler<Args>,
     {
-        let boxed_handler = Box::new(move |ctx| {
-            Box::pin(handler(ctx)) as Pin<Box<dyn Future<Output = HandlerResult> + Send>>
-        });
+        self.insert_route(None, path, handler)
+    }
+
+    pub fn route<H, Args>(&mut self, method: &str, path: &str, handler: H) -> RouteHandle<'_>
+    where
+        H: Handler<Args>,
+    {
+        self.insert_route(Some(method.to_ascii_uppercase()), path, handler)
+    }
 
-        self.routes.insert(path.to_string(), boxed_handler);
+    pub fn get<H, Args>(&mut self, path: &str, handler: H) -> RouteHandle<'_>
+    where
+        H: Handler<Args>,
+    {
+        self.route("GET", path, handler)
     }
 
-    pub fn get<F, Fut>(&mut self, path: &str, handler: F)
+    pub fn post<H, Args>(&mut self, path: &str, handler: H) -> RouteHandle<'_>
     where
-        F: Fn(Context) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = HandlerResult> + Send + 'static,
+        H: Handler<Args>,
     {
-        self.add_route(path, handler);
+        self.route("POST", path, handler)
     }
 
-    pub fn post<F, Fut>(&mut self, path: &str, handler: F)
+    pub fn put<H, Args>(&mut self, path: &str, handler: H) -> RouteHandle<'_>
     where
-        F: Fn(Context) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = HandlerResult> + Send + 'static,
+        H: Handler<Args>,
     {
-        self.add_route(path, handler);
+        self.route("PUT", path, handler)
+    }
+
+    pub fn patch<H, Args>(&mut self, path: &str, handler: H) -> RouteHandle<'_>
+    where
+        H: Handler<Args>,
+    {
+        self.route("PATCH", path, handler)
+    }
+
+    pub fn delete<H, Args>(&mut self, path: &str, handler: H) -> RouteHandle<'_>
+    where
+        H: Handler<Args>,
+    {
+        self.route("DELETE", path, handler)
+    }
+
+    pub fn nest(&mut self, prefix: &str, router: Router) {
+        let Router {
+            routes,
+            middlewares,
+            cookie_keys,
+            state,
+            names,
+            openapi,
+            slash_policy,
+        } = router;
+
+        if slash_policy != SlashPolicy::Strict && slash_policy != self.slash_policy {
+            panic!(
+                "Router nested at `{}` sets slash policy {:?}, set it on the outer router instead",
+                prefix, slash_policy
+            );
+        }
+        match (&self.cookie_keys, cookie_keys) {
+            (Some(keys), Some(nested)) if !Arc::ptr_eq(keys, &nested) => panic!(
+                "Router nested at `{}` has its own cookie keys, set them on the outer router instead",
+                prefix
+            ),
+            (None, nested) => self.cookie_keys = nested,
+            _ => {}
+        }
+
+        for mut route in routes {
+            route.pattern = route.pattern.prefixed(prefix);
+            route.middlewares.splice(0..0, middlewares.iter().cloned());
+            self.push_route(route);
+        }
+
+        for (name, pattern) in names.iter() {
+            add_name(&mut self.names, name, &pattern.prefixed(prefix));
+        }
+        if self.openapi.is_none() {
+            self.openapi = openapi.map(|endpoint| OpenApiEndpoint {
+                path: PathPattern::parse(&endpoint.path)
+                    .prefixed(prefix)
+                    .as_str()
+                    .to_string(),
+                ..endpoint
+            });
+            for route in self.routes.iter() {
+                check_openapi(self.openapi.as_ref(), route);
+            }
+        }
+        let mut merged = state;
+        state_mut(&mut merged).extend(std::mem::take(state_mut(&mut self.state)));
+        self.state = merged;
+    }
+
+    pub fn group(&mut self, prefix: &str, build: impl FnOnce(&mut Router)) {
+        let mut group = Router::new();
+        build(&mut group);
+        self.nest(prefix, group);
+    }
+
+    pub fn routes(&self) -> impl Iterator<Item = RouteInfo<'_>> {
+        self.routes.iter().map(Route::info)
+    }
+
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, UrlError> {
+        url_for(&self.names, name, params)
+    }
+
+    pub fn openapi(&self, title: &str, version: &str) -> Value {
+        openapi::document(&self.routes, title, version)
+    }
+
+    pub fn serve_openapi(&mut self, path: &str, title: &str, version: &str) {
+        self.openapi = Some(OpenApiEndpoint {
+            path: path.to_string(),
+            title: title.to_string(),
+            version: version.to_string(),
+            document: OnceLock::new(),
+        });
+        for route in self.routes.iter() {
+            check_openapi(self.openapi.as_ref(), route);
+        }
+    }
+
+    fn insert_route<H, Args>(
+        &mut self,
+        method: Option<String>,
+        path: &str,
+        handler: H,
+    ) -> RouteHandle<'_>
+    where
+        H: Handler<Args>,
+    {
+        let index = self.push_route(Route {
+            method,
+            pattern: PathPattern::parse(path),
+            handler: Box::new(move |ctx| handler.call(ctx)),
+            handler_name: std::any::type_name::<H>(),
+            middlewares: Vec::new(),
+            docs: RouteDocs::default(),
+        });
+        RouteHandle::new(&mut self.routes[index], &mut self.names)
     }
 
-    pub async fn handle_request(
-        &self,
-        request: Request,
-        mut response: Response,
-        mut wr: WriteHalf<TcpStream>,
-    ) {
-        let path = &request.request_line.request_target;
+    fn push_route(&mut self, route: Route) -> usize {
+        check_openapi(self.openapi.as_ref(), &route);
+        if let Some(existing) = self
+            .routes
+            .iter()
+            .find(|existing| existing.conflicts_with(&route))
+        {
+            panic!(
+                "Route `{}` conflicts with `{}`",
+                route.info(),
+                existing.info()
+            );
+        }
+        let position = self
+            .routes
+            .iter()
+            .position(|existing| route.pattern.specificity(&existing.pattern).is_lt())
+            .unwrap_or(self.routes.len());
+        self.routes.insert(position, route);
+        position
+    }
+
+    pub async fn handle_request(&self, mut request: Request, response: Response) -> Response {
+        if self.slash_policy != SlashPolicy::Strict
+            && request.request_line.request_target.starts_with('/')
+        {
+            let target = &request.request_line.request_target;
+            let (path, query) = match target.split_once('?') {
+                Some((path, query)) => (path, Some(query)),
+                None => (target.as_str(), None),
+            };
+            if let Cow::Owned(path) = normalize(path) {
+                let target = match query {
+                    Some(query) => format!("{}?{}", path, query),
+                    None => path,
+                };
+                if self.slash_policy == SlashPolicy::Redirect {
+                    return Response::permanent_redirect(&target)
+                        .unwrap_or_else(|_| Status::BAD_REQUEST.into_response());
+                }
+                request.request_line.request_target = target;
+            }
+        }
+
+        let context = Context {
+            request,
+            response,
+            cookie_keys: self.cookie_keys.clone(),
+            state: self.state.clone(),
+            names: self.names.clone(),
+        };
+        Next::new(self, &self.middlewares).run(context).await
+    }
 
-        if let Some(handler) = self.routes.get(path) {
-            let context = Context { request, response };
+    pub(crate) async fn dispatch(&self, mut context: Context) -> Response {
+        let target = &context.request.request_line.request_target;
+        let path = target
+            .split_once('?')
+            .map_or(target.as_str(), |(path, _)| path);
+        let method = context.request.request_line.method.as_str();
 
-            let rc = &context.request.clone();
-            let mut res = handler(context).await.unwrap();
-            let encoding = rc.headers.get("Accept-Encoding");
-            wr.write_all(&res.send(encoding)).await.unwrap();
+        if let Some(endpoint) = &self.openapi
+            && endpoint.path == path
+            && matches!(method, "GET" | "HEAD")
+        {
+            let document = endpoint.document.get_or_init(|| {
+                serde_json::to_vec(&self.openapi(&endpoint.title, &endpoint.version))
+                    .unwrap_or_default()
+            });
+            let mut response = context.response;
+            response.body(document.clone());
+            response
+                .set_header("Content-Type", "application/json")
+                .unwrap();
+            return response;
+        }
+
+        let mut allowed: Vec<&str> = Vec::new();
+        for route in self.routes.iter() {
+            let Some(params) = route.pattern.matches(path) else {
+                continue;
+            };
+            if !route.accepts(method) {
+                if let Some(expected) = route.method.as_deref() {
+                    let head = (expected == "GET").then_some("HEAD");
+                    for method in std::iter::once(expected).chain(head) {
+                        if !allowed.contains(&method) {
+                            allowed.push(method);
+                        }
+                    }
+                }
+                continue;
+            }
+            context.insert(params);
+            return Next::handler(&route.handler, &route.middlewares)
+                .run(context)
+                .await;
+        }
+
+        let mut response = context.response;
+        if allowed.is_empty() {
+            response.status(Status::NOT_FOUND);
         } else {
-            response.status(Status::NotFound);
+            response.status(Status::METHOD_NOT_ALLOWED);
+            response.set_header("Allow", &allowed.join(", ")).unwrap();
+        }
+        response
+    }
+}
+
+fn check_openapi(endpoint: Option<&OpenApiEndpoint>, route: &Route) {
+    if let Some(endpoint) = endpoint
+        && route.accepts("GET")
+        && route.pattern.matches(&endpoint.path).is_some()
+    {
+        panic!(
+            "Route `{}` conflicts with the OpenAPI document at `{}`",
+            route.info(),
+            endpoint.path
+        );
+    }
+}
+
+fn state_mut(state: &mut Arc<Extensions>) -> &mut Extensions {
+    Arc::get_mut(state).expect("router state cannot change while a request holds it")
+}
+
+fn url_for(names: &RouteNames, name: &str, params: &[(&str, &str)]) -> Result<String, UrlError> {
+    let pattern = names
+        .get(name)
+        .ok_or_else(|| UrlError::UnknownRoute(name.to_string()))?;
+    let mut url = pattern.render(params)?;
+
+    let query: Vec<&(&str, &str)> = params
+        .iter()
+        .filter(|(param, _)| !pattern.params().any(|name| name == *param))
+        .collect();
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(&serde_urlencoded::to_string(query).unwrap_or_default());
+    }
+    Ok(url)
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cookie::Key;
+    use crate::headers::Headers;
+    use crate::request::{request::RequestLine, request_from_reader};
+    use crate::router::Path;
+    use crate::router::middleware::BoxFuture;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn router() -> Router {
+        let mut router = Router::new();
+        router.set_cookie_keys(CookieKeys::new(Key::derive(&[7; 32]).unwrap()));
+        router.get("/login", |mut ctx: Context| async move {
+            ctx.add_signed_cookie(Cookie::new("user", "alice").http_only(true))?;
+            ctx.add_private_cookie(Cookie::new("cart", "3 coffees"))?;
+            ctx.add_signed_cookie(Cookie::new("prefs", "dark mode, \"wide\""))?;
+            Ok(ctx.response)
+        });
+        router.get("/me", |ctx: Context| async move {
+            let user = ctx.signed_cookie("user").ok_or(HandlerError::BadRequest)?;
+            let cart = ctx.private_cookie("cart").ok_or(HandlerError::BadRequest)?;
+            let prefs = ctx.signed_cookie("prefs").ok_or(HandlerError::BadRequest)?;
+            let mut response = ctx.response;
+            response.body(format!("{user}: {cart} ({prefs})").into_bytes());
+            Ok(response)
+        });
+        router
+    }
+
+    async fn request(target: &str, cookie: &str) -> Request {
+        let raw = format!("GET {target} HTTP/1.1\r\nCookie: {cookie}\r\n\r\n");
+        request_from_reader(raw.as_bytes()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn signed_and_private_cookies_round_trip() {
+        let router = router();
+        let login = router
+            .handle_request(request("/login", "").await, Response::new())
+            .await;
+        let cookies: Vec<&str> = login
+            .headers()
+            .get_all("set-cookie")
+            .filter_map(|cookie| cookie.split(';').next())
+            .collect();
+        assert!(cookies[0].starts_with("user=YWxpY2U."));
+        assert!(!cookies[1].contains("coffees"));
+        assert_eq!(cookies.len(), 3);
+
+        let me = router
+            .handle_request(request("/me", &cookies.join("; ")).await, Response::new())
+            .await;
+        assert_eq!(me.status_code(), 200);
+        assert_eq!(me.body_bytes(), b"alice: 3 coffees (dark mode, \"wide\")");
+
+        let forged = cookies.join("; ").replace("YWxpY2U", "YWRtaW4");
+        let me = router
+            .handle_request(request("/me", &forged).await, Response::new())
+            .await;
+        assert_eq!(me.status_code(), 400);
+    }
+
+    struct RequestId(usize);
+
+    struct Counter(AtomicUsize);
+
+    struct AssignRequestId;
+
+    impl Middleware for AssignRequestId {
+        fn handle<'a>(&'a self, mut ctx: Context, next: Next<'a>) -> BoxFuture<'a, Response> {
+            Box::pin(async move {
+                let counter = ctx.state::<Counter>().unwrap();
+                ctx.insert(RequestId(counter.0.fetch_add(1, Ordering::SeqCst)));
+                next.run(ctx).await
+            })
         }
     }
+
+    #[tokio::test]
+    async fn handlers_see_state_and_request_extensions() {
+        let mut router = Router::new();
+        router.add_state(Counter(AtomicUsize::new(1)));
+        router.add_middleware(AssignRequestId);
+        router.get("/id", |ctx: Context| async move {
+            let id = ctx.get::<RequestId>().ok_or(HandlerError::InternalError)?;
+            let user = ctx
+                .get::<String>()
+                .map_or("anonymous", |user| user.as_str());
+            let body = format!("{} {}", id.0, user);
+            let mut response = ctx.response;
+            response.body(body.into_bytes());
+            Ok(response)
+        });
+
+        let first = router
+            .handle_request(request("/id", "").await, Response::new())
+            .await;
+        assert_eq!(first.body_bytes(), b"1 anonymous");
+
+        let mut request = request("/id", "").await;
+        request.extensions.insert("alice".to_string());
+        let second = router.handle_request(request, Response::new()).await;
+        assert_eq!(second.body_bytes(), b"2 alice");
+    }
+
+    struct Tag(&'static str);
+
+    impl Middleware for Tag {
+        fn handle<'a>(&'a self, ctx: Context, next: Next<'a>) -> BoxFuture<'a, Response> {
+            Box::pin(async move {
+                let mut response = next.run(ctx).await;
+                response.append_header("X-Tag", self.0).unwrap();
+                response
+            })
+        }
+    }
+
+    async fn send(router: &Router, method: &str, target: &str) -> Response {
+        let raw = format!("{method} {target} HTTP/1.1\r\n\r\n");
+        let request = request_from_reader(raw.as_bytes()).await.unwrap();
+        router.handle_request(request, Response::new()).await
+    }
+
+    #[tokio::test]
+    async fn nested_routers_and_groups_keep_their_middleware() {
+        let mut users = Router::new();
+        users.add_middleware(Tag("users"));
+        users.get("/", || async { "list" });
+        users.get("/{id}", |Path(id): Path<u32>| async move {
+            format!("user {id}")
+        });
+        users.post("/{id}", || async { "updated" });
+
+        let mut api = Router::new();
+        api.add_middleware(Tag("api"));
+        api.nest("/users", users);
+
+        let mut router = Router::new();
+        router.nest("/api/v1/", api);
+        router.group("/admin", |admin| {
+            admin.add_middleware(Tag("admin"));
+            admin.get("/stats", || async { "stats" });
+        });
+
+        let response = send(&router, "GET", "/api/v1/users/7").await;
+        assert_eq!(response.body_bytes(), b"user 7");
+        assert_eq!(
+            response.headers().get_all("x-tag").collect::<Vec<_>>(),
+            ["users", "api"]
+        );
+        assert_eq!(
+            send(&router, "GET", "/api/v1/users").await.body_bytes(),
+            b"list"
+        );
+        assert_eq!(
+            send(&router, "POST", "/api/v1/users/7").await.body_bytes(),
+            b"updated"
+        );
+
+        let response = send(&router, "DELETE", "/api/v1/users/7").await;
+        assert_eq!(response.status_code(), 405);
+        assert_eq!(response.headers().get("allow"), Some("GET, HEAD, POST"));
+
+        let response = send(&router, "GET", "/admin/stats").await;
+        assert_eq!(response.headers().get("x-tag"), Some("admin"));
+        assert_eq!(send(&router, "GET", "/stats").await.status_code(), 404);
+    }
+
+    #[test]
+    #[should_panic(expected = "Route `GET /users/{name}` conflicts with `GET /users/{id}`")]
+    fn conflicting_routes_panic_at_registration() {
+        let mut router = Router::new();
+        router.get("/users/{id}", || async { "id" });
+        router.get("/users/me", || async { "me" });
+        router.post("/users/{name}", || async { "created" });
+        router.get("/users/{name}", || async { "name" });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Router nested at `/api` sets slash policy Redirect, set it on the outer router instead"
+    )]
+    fn nesting_rejects_a_different_slash_policy() {
+        let mut api = Router::new();
+        api.set_slash_policy(SlashPolicy::Redirect);
+
+        let mut router = Router::new();
+        router.set_slash_policy(SlashPolicy::Normalize);
+        router.nest("/api", api);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Router nested at `/api` has its own cookie keys, set them on the outer router instead"
+    )]
+    fn nesting_rejects_other_cookie_keys() {
+        let mut api = Router::new();
+        api.set_cookie_keys(CookieKeys::new(Key::derive(&[1; 32]).unwrap()));
+
+        let mut router = Router::new();
+        router.set_cookie_keys(CookieKeys::new(Key::derive(&[2; 32]).unwrap()));
+        router.nest("/api", api);
+    }
+
+    #[test]
+    #[should_panic(expected = "Route `GET /api/status` conflicts with `GET /api/status`")]
+    fn nesting_detects_conflicts() {
+        let mut api = Router::new();
+        api.get("/status", || async { "nested" });
+
+        let mut router = Router::new();
+        router.get("/api/status", || async { "top" });
+        router.nest("/api", api);
+    }
+
+    #[tokio::test]
+    async fn builds_urls_for_named_routes() {
+        let mut posts = Router::new();
+        posts.get("/{slug}", || async { "post" }).name("posts.show");
+
+        let mut router = Router::new();
+        router
+            .get("/users/{id}", || async { "user" })
+            .name("users.show");
+        router.nest("/blog", posts);
+        router.post("/login", |ctx: Context| async move {
+            let location = ctx
+                .url_for("users.show", &[("id", "42"), ("tab", "a&b")])
+                .map_err(|_| HandlerError::InternalError)?;
+            let mut response = ctx.response;
+            response.set_header("Location", &location)?;
+            Ok(response)
+        });
+
+        assert_eq!(
+            router.url_for("posts.show", &[("slug", "hello world")]),
+            Ok("/blog/hello%20world".to_string())
+        );
+        assert_eq!(
+            router.url_for("users.show", &[]),
+            Err(UrlError::MissingParam("id".to_string()))
+        );
+        assert_eq!(
+            router.url_for("users.edit", &[("id", "1")]),
+            Err(UrlError::UnknownRoute("users.edit".to_string()))
+        );
+
+        let response = send(&router, "POST", "/login").await;
+        assert_eq!(
+            response.headers().get("location"),
+            Some("/users/42?tab=a%26b")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Route name `home` is already used by `/`")]
+    fn duplicate_route_names_panic() {
+        let mut router = Router::new();
+        router.get("/", || async { "home" }).name("home");
+        router.get("/index", || async { "index" }).name("home");
+    }
+
+    #[tokio::test]
+    async fn strict_routes_keep_a_trailing_slash() {
+        let mut router = Router::new();
+        router.get("/users/", |_: Context| async { Ok(Response::new()) });
+        router.get("/users", |_: Context| async {
+            Ok(Status::ACCEPTED.into_response())
+        });
+
+        assert_eq!(send(&router, "GET", "/users/").await.status_code(), 200);
+        assert_eq!(send(&router, "GET", "/users").await.status_code(), 202);
+        assert_eq!(send(&router, "GET", "/users//").await.status_code(), 404);
+    }
+
+    #[tokio::test]
+    async fn slash_policy_redirects_or_normalizes() {
+        let mut router = Router::new();
+        router.get("/users/{id}", |Path(id): Path<u32>| async move {
+            format!("user {id}")
+        });
+
+        assert_eq!(send(&router, "GET", "/users/7/").await.status_code(), 404);
+
+        router.set_slash_policy(SlashPolicy::Redirect);
+        let response = send(&router, "POST", "//users//7/?tab=posts").await;
+        assert_eq!(response.status_code(), 308);
+        assert_eq!(
+            response.headers().get("location"),
+            Some("/users/7?tab=posts")
+        );
+        assert_eq!(send(&router, "GET", "/users/7").await.status_code(), 200);
+        for (method, target) in [("OPTIONS", "*"), ("GET", "http://a/users//7/")] {
+            let request = Request {
+                request_line: RequestLine::new("2", target, method),
+                headers: Headers::new(),
+                body: None,
+                extensions: Extensions::new(),
+            };
+            let response = router.handle_request(request, Response::new()).await;
+            assert_eq!(response.status_code(), 404);
+        }
+
+        router.set_slash_policy(SlashPolicy::Normalize);
+        let response = send(&router, "GET", "/users//7/").await;
+        assert_eq!(response.body_bytes(), b"user 7");
+    }
 }
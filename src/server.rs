@@ -1,4 +1,9 @@
 pub mod server;
 pub mod lifecycle;
+pub mod config;
+pub mod limiter;
+pub mod http2;
+pub mod tls;
 pub use lifecycle::{ServerState, LifecycleManager};
-pub use server::Server;
\ No newline at end of file
+pub use config::{Http2Config, OverloadBehavior, ServerConfig, TlsConfig};
+pub use server::Server;
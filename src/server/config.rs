@@ -0,0 +1,75 @@
+use std::{path::PathBuf, time::Duration};
+
+use crate::request::request::MAX_BODY_SIZE;
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub max_connections: usize,
+    pub max_connections_per_ip: Option<usize>,
+    pub overload: OverloadBehavior,
+    pub max_queued_connections: usize,
+    pub retry_after: Duration,
+    pub max_body_size: usize,
+    pub header_read_timeout: Option<Duration>,
+    pub body_read_timeout: Option<Duration>,
+    pub handler_timeout: Option<Duration>,
+    pub write_timeout: Option<Duration>,
+    pub keep_alive_timeout: Option<Duration>,
+    pub tls: Option<TlsConfig>,
+    pub http2: Http2Config,
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct Http2Config {
+    pub enabled: bool,
+    pub max_concurrent_streams: u32,
+    pub initial_window_size: u32,
+    pub initial_connection_window_size: u32,
+    pub max_frame_size: u32,
+    pub max_header_list_size: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverloadBehavior {
+    Queue,
+    Reject,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10000,
+            max_connections_per_ip: None,
+            overload: OverloadBehavior::Queue,
+            max_queued_connections: 1024,
+            retry_after: Duration::from_secs(1),
+            max_body_size: MAX_BODY_SIZE,
+            header_read_timeout: Some(Duration::from_secs(10)),
+            body_read_timeout: Some(Duration::from_secs(30)),
+            handler_timeout: Some(Duration::from_secs(60)),
+            write_timeout: Some(Duration::from_secs(30)),
+            keep_alive_timeout: Some(Duration::from_secs(5)),
+            tls: None,
+            http2: Http2Config::default(),
+        }
+    }
+}
+
+impl Default for Http2Config {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_concurrent_streams: 100,
+            initial_window_size: 65_535,
+            initial_connection_window_size: 1024 * 1024,
+            max_frame_size: 16_384,
+            max_header_list_size: 16 * 1024,
+        }
+    }
+}
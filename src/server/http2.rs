@@ -0,0 +1,678 @@
+use std::{
+    future::poll_fn,
+    io::IoSlice,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use bytes::Bytes;
+use h2::{
+    RecvStream,
+    server::{self, SendResponse},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, BufReader, ReadBuf},
+    task::JoinSet,
+    time::Instant,
+};
+use tracing::warn;
+
+use crate::{
+    extensions::Extensions,
+    headers::{Headers, parser::decode_value, typed::Connection},
+    request::{
+        Body,
+        request::{Request, RequestLine},
+    },
+    response::{Response, Status},
+    router::router::Router,
+    server::{
+        config::ServerConfig,
+        server::{deadline, within},
+    },
+};
+
+pub const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+const CONNECTION_HEADERS: [&str; 5] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+];
+
+const FRAME_HEADERS: u8 = 0x1;
+const FRAME_SETTINGS: u8 = 0x4;
+const FLAG_END_STREAM: u8 = 0x1;
+const FLAG_ACK: u8 = 0x1;
+const FLAG_END_HEADERS: u8 = 0x4;
+
+const UPGRADE_SKIPPED_HEADERS: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+    "http2-settings",
+    "host",
+    "te",
+];
+
+pub fn is_preface(buffer: &[u8]) -> bool {
+    buffer.starts_with(PREFACE)
+}
+
+pub async fn read_preface<S>(io: &mut S, deadline: Option<Instant>) -> std::io::Result<Vec<u8>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut prefix = Vec::with_capacity(PREFACE.len());
+    while prefix.len() < PREFACE.len() && PREFACE.starts_with(&prefix) {
+        match within(deadline, io.read_buf(&mut prefix)).await {
+            Ok(Ok(0)) | Err(_) => break,
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => return Err(e),
+        }
+    }
+    Ok(prefix)
+}
+
+pub struct Upgrade {
+    settings: Vec<u8>,
+    headers: Vec<u8>,
+}
+
+impl Upgrade {
+    pub fn from_request(request: &Request, config: &ServerConfig) -> Option<Self> {
+        let wants_h2c = request.request_line.http_version == "1.1"
+            && request
+                .headers
+                .get_all("upgrade")
+                .flat_map(|value| value.split(','))
+                .any(|protocol| protocol.trim().eq_ignore_ascii_case("h2c"))
+            && request
+                .headers
+                .typed_get::<Connection>()
+                .ok()
+                .flatten()
+                .is_some_and(|connection| {
+                    connection.contains("upgrade") && connection.contains("http2-settings")
+                });
+        if !wants_h2c || request.body.as_ref().is_some_and(|body| !body.is_empty()) {
+            return None;
+        }
+
+        let mut values = request.headers.get_all("http2-settings");
+        let (Some(settings), None) = (values.next(), values.next()) else {
+            return None;
+        };
+        let settings = URL_SAFE_NO_PAD
+            .decode(settings.trim_end_matches('='))
+            .ok()?;
+        if settings.len() % 6 != 0 {
+            return None;
+        }
+
+        let headers = encode_headers(request);
+        if headers.len() > config.http2.max_frame_size as usize {
+            return None;
+        }
+        let mut frame = frame_header(
+            headers.len(),
+            FRAME_HEADERS,
+            FLAG_END_STREAM | FLAG_END_HEADERS,
+            1,
+        )
+        .to_vec();
+        frame.extend_from_slice(&headers);
+        Some(Self {
+            settings,
+            headers: frame,
+        })
+    }
+}
+
+pub async fn serve_upgrade<S>(
+    mut io: BufReader<S>,
+    upgrade: Upgrade,
+    router: Arc<Router>,
+    config: Arc<ServerConfig>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let deadline = deadline(config.header_read_timeout);
+    let mut start = [0; PREFACE.len() + 9];
+    match within(deadline, io.read_exact(&mut start)).await {
+        Ok(result) => result?,
+        Err(_) => return Err("HTTP/2 preface timed out".into()),
+    };
+    let (preface, header) = start.split_at(PREFACE.len());
+    let length = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+    if preface != PREFACE
+        || header[3] != FRAME_SETTINGS
+        || header[4] & FLAG_ACK != 0
+        || header[5..] != [0; 4]
+        || upgrade.settings.len() + length > config.http2.max_frame_size as usize
+    {
+        return Err("expected the HTTP/2 preface and SETTINGS after the upgrade".into());
+    }
+
+    let mut settings = upgrade.settings;
+    let offset = settings.len();
+    settings.resize(offset + length, 0);
+    match within(deadline, io.read_exact(&mut settings[offset..])).await {
+        Ok(result) => result?,
+        Err(_) => return Err("HTTP/2 preface timed out".into()),
+    };
+
+    let mut prefix = PREFACE.to_vec();
+    prefix.extend_from_slice(&frame_header(settings.len(), FRAME_SETTINGS, 0, 0));
+    prefix.extend_from_slice(&settings);
+    prefix.extend_from_slice(&upgrade.headers);
+    serve_connection(Rewind::new(prefix, io), router, config).await
+}
+
+fn frame_header(length: usize, kind: u8, flags: u8, stream: u32) -> [u8; 9] {
+    let length = (length as u32).to_be_bytes();
+    let stream = stream.to_be_bytes();
+    [
+        length[1], length[2], length[3], kind, flags, stream[0], stream[1], stream[2], stream[3],
+    ]
+}
+
+fn encode_headers(request: &Request) -> Vec<u8> {
+    let mut block = Vec::new();
+    let line = &request.request_line;
+    let mut fields = vec![
+        (":method", line.method.as_str()),
+        (":scheme", "http"),
+        (":path", line.request_target.as_str()),
+    ];
+    if let Some(host) = request.headers.get("host") {
+        fields.push((":authority", host));
+    }
+    for (name, value) in fields {
+        encode_field(&mut block, name, value);
+    }
+    for (name, value) in request.headers.iter() {
+        let name = name.to_ascii_lowercase();
+        if !UPGRADE_SKIPPED_HEADERS.contains(&name.as_str()) {
+            encode_field(&mut block, &name, value);
+        }
+    }
+    block
+}
+
+fn encode_field(block: &mut Vec<u8>, name: &str, value: &str) {
+    block.push(0);
+    for string in [name.as_bytes(), value.as_bytes()] {
+        encode_integer(block, string.len());
+        block.extend_from_slice(string);
+    }
+}
+
+fn encode_integer(block: &mut Vec<u8>, mut value: usize) {
+    const PREFIX: usize = 0x7f;
+    if value < PREFIX {
+        block.push(value as u8);
+        return;
+    }
+    block.push(PREFIX as u8);
+    value -= PREFIX;
+    while value >= 0x80 {
+        block.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    block.push(value as u8);
+}
+
+pub struct Rewind<S> {
+    prefix: Bytes,
+    inner: S,
+}
+
+impl<S> Rewind<S> {
+    pub fn new(prefix: impl Into<Bytes>, inner: S) -> Self {
+        Self {
+            prefix: prefix.into(),
+            inner,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Rewind<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if !self.prefix.is_empty() {
+            let length = self.prefix.len().min(buf.remaining());
+            buf.put_slice(&self.prefix.split_to(length));
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Rewind<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+pub async fn serve_connection<S>(
+    io: S,
+    router: Arc<Router>,
+    config: Arc<ServerConfig>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let settings = &config.http2;
+    let mut connection = match within(
+        deadline(config.header_read_timeout),
+        server::Builder::new()
+            .max_concurrent_streams(settings.max_concurrent_streams)
+            .initial_window_size(settings.initial_window_size)
+            .initial_connection_window_size(settings.initial_connection_window_size)
+            .max_frame_size(settings.max_frame_size)
+            .max_header_list_size(settings.max_header_list_size)
+            .handshake::<_, Bytes>(io),
+    )
+    .await
+    {
+        Ok(result) => result?,
+        Err(_) => return Err("HTTP/2 handshake timed out".into()),
+    };
+
+    let mut streams = JoinSet::new();
+    loop {
+        let idle_deadline = if streams.is_empty() {
+            deadline(config.keep_alive_timeout)
+        } else {
+            None
+        };
+        tokio::select! {
+            accepted = within(idle_deadline, connection.accept()) => match accepted {
+                Err(_) => break,
+                Ok(None) => return Ok(()),
+                Ok(Some(result)) => {
+                    let (request, respond) = result?;
+                    let router = Arc::clone(&router);
+                    let config = Arc::clone(&config);
+                    streams.spawn(async move {
+                        if let Err(e) = handle_stream(request, respond, router, config).await {
+                            warn!("Erreur on HTTP/2 stream: {}", e);
+                        }
+                    });
+                }
+            },
+            Some(_) = streams.join_next() => {}
+        }
+    }
+
+    connection.graceful_shutdown();
+    let drain = async { while let Some(Ok(_)) = connection.accept().await {} };
+    let _ = within(deadline(config.write_timeout), drain).await;
+    Ok(())
+}
+
+async fn handle_stream(
+    request: hyperium_http::Request<RecvStream>,
+    mut respond: SendResponse<Bytes>,
+    router: Arc<Router>,
+    config: Arc<ServerConfig>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (parts, mut stream) = request.into_parts();
+
+    let content = match within(
+        deadline(config.body_read_timeout),
+        read_stream(&mut stream, config.max_body_size),
+    )
+    .await
+    {
+        Ok(Ok(Some(content))) => content,
+        Ok(Ok(None)) => {
+            return send_response(
+                &mut respond,
+                error_response(Status::CONTENT_TOO_LARGE),
+                None,
+                false,
+                &config,
+            )
+            .await;
+        }
+        Ok(Err(e)) => return Err(e.into()),
+        Err(_) => {
+            return send_response(
+                &mut respond,
+                error_response(Status::REQUEST_TIMEOUT),
+                None,
+                false,
+                &config,
+            )
+            .await;
+        }
+    };
+
+    let request = match to_request(parts, content) {
+        Some(request) => request,
+        None => {
+            return send_response(
+                &mut respond,
+                error_response(Status::BAD_REQUEST),
+                None,
+                false,
+                &config,
+            )
+            .await;
+        }
+    };
+    let encoding = request.headers.get_combined("Accept-Encoding");
+    let head = request.request_line.method == "HEAD";
+
+    let response = match within(
+        deadline(config.handler_timeout),
+        router.handle_request(request, Response::new()),
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(_) => {
+            warn!("Handler timed out");
+            error_response(Status::GATEWAY_TIMEOUT)
+        }
+    };
+    send_response(&mut respond, response, encoding.as_deref(), head, &config).await
+}
+
+async fn read_stream(stream: &mut RecvStream, limit: usize) -> Result<Option<Vec<u8>>, h2::Error> {
+    let mut content = Vec::new();
+    while let Some(chunk) = stream.data().await {
+        let chunk = chunk?;
+        let _ = stream.flow_control().release_capacity(chunk.len());
+        if chunk.len() > limit - content.len() {
+            return Ok(None);
+        }
+        content.extend_from_slice(&chunk);
+    }
+    Ok(Some(content))
+}
+
+fn to_request(parts: hyperium_http::request::Parts, content: Vec<u8>) -> Option<Request> {
+    let target = parts
+        .uri
+        .path_and_query()
+        .map_or("/", |path_and_query| path_and_query.as_str());
+
+    let mut headers = Headers::new();
+    if let Some(authority) = parts.uri.authority() {
+        let hosts = parts.headers.get_all("host");
+        if hosts.iter().next().is_none() {
+            headers.append("Host", authority.as_str()).ok()?;
+        } else if hosts.iter().any(|host| {
+            !host
+                .as_bytes()
+                .eq_ignore_ascii_case(authority.as_str().as_bytes())
+        }) {
+            return None;
+        }
+    }
+    for (name, value) in parts.headers.iter() {
+        headers
+            .append(name.as_str(), &decode_value(value.as_bytes()))
+            .ok()?;
+    }
+
+    let body = if content.is_empty() {
+        None
+    } else {
+        Some(Body::new(content))
+    };
+
+    Some(Request {
+        request_line: RequestLine::new("2", target, parts.method.as_str()),
+        headers,
+        body,
+        extensions: Extensions::new(),
+    })
+}
+
+fn error_response(status: Status) -> Response {
+    let mut response = Response::new();
+    response.status(status);
+    response
+}
+
+async fn send_response(
+    respond: &mut SendResponse<Bytes>,
+    mut response: Response,
+    accept_encoding: Option<&str>,
+    head: bool,
+    config: &ServerConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    response.prepare(accept_encoding)?;
+    let mut builder = hyperium_http::Response::builder().status(response.status_code());
+    for (name, value) in response.headers().iter() {
+        if !CONNECTION_HEADERS
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case(name))
+        {
+            builder = builder.header(name, value);
+        }
+    }
+
+    let mut data = if head || !response.body_allowed() {
+        Bytes::new()
+    } else {
+        Bytes::copy_from_slice(response.body_bytes())
+    };
+    let mut stream = respond.send_response(builder.body(())?, data.is_empty())?;
+
+    let send_body = async {
+        while !data.is_empty() {
+            stream.reserve_capacity(data.len());
+            let capacity = match poll_fn(|cx| stream.poll_capacity(cx)).await {
+                Some(capacity) => capacity?,
+                None => return Err("Stream closed before the body was sent".into()),
+            };
+            if capacity == 0 {
+                continue;
+            }
+            let chunk = data.split_to(capacity.min(data.len()));
+            stream.send_data(chunk, data.is_empty())?;
+        }
+        Ok::<(), Box<dyn std::error::Error>>(())
+    };
+
+    match within(deadline(config.write_timeout), send_body).await {
+        Ok(result) => result,
+        Err(_) => Err("write timed out".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::typed::Host;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    #[test]
+    fn detects_prior_knowledge_preface() {
+        assert!(is_preface(PREFACE));
+        assert!(!is_preface(b"PRI * HTTP/2.0\r\n"));
+        assert!(!is_preface(b"GET / HTTP/1.1\r\n"));
+        assert!(!is_preface(b"PUT / HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn converts_h2_request() {
+        let (parts, _) = hyperium_http::Request::builder()
+            .method("POST")
+            .uri("https://localhost:3333/coffee?size=large")
+            .header("content-type", "application/json")
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let request = to_request(parts, b"{}".to_vec()).unwrap();
+        assert_eq!(request.request_line.method, "POST");
+        assert_eq!(request.request_line.http_version, "2");
+        assert_eq!(request.request_line.request_target, "/coffee?size=large");
+        assert_eq!(request.headers.get("host").unwrap(), "localhost:3333");
+        assert_eq!(
+            request.headers.get("content-type").unwrap(),
+            "application/json"
+        );
+        assert_eq!(request.body.unwrap().to_string_lossy(), "{}");
+    }
+
+    #[test]
+    fn host_must_agree_with_authority() {
+        let parts = |host: &str| {
+            hyperium_http::Request::get("https://localhost:3333/")
+                .header("host", host)
+                .body(())
+                .unwrap()
+                .into_parts()
+                .0
+        };
+
+        let request = to_request(parts("LOCALHOST:3333"), Vec::new()).unwrap();
+        assert_eq!(request.headers.get_all("host").count(), 1);
+        assert!(request.headers.typed_get::<Host>().is_ok());
+
+        assert!(to_request(parts("example.com"), Vec::new()).is_none());
+    }
+
+    #[test]
+    fn upgrade_requires_both_connection_options() {
+        let request = |connection: &str| {
+            let mut headers = Headers::new();
+            headers.append("Host", "localhost").unwrap();
+            headers.append("Connection", connection).unwrap();
+            headers.append("Upgrade", "h2c").unwrap();
+            headers
+                .append("HTTP2-Settings", "AAMAAABkAAQAAP__")
+                .unwrap();
+            Request {
+                request_line: RequestLine::new("1.1", "/", "GET"),
+                headers,
+                body: None,
+                extensions: Extensions::new(),
+            }
+        };
+        let config = ServerConfig::default();
+
+        assert!(Upgrade::from_request(&request("Upgrade, HTTP2-Settings"), &config).is_some());
+        assert!(Upgrade::from_request(&request("Upgrade"), &config).is_none());
+        assert!(Upgrade::from_request(&request("HTTP2-Settings"), &config).is_none());
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_whole_preface() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let read = async {
+            client.write_all(&PREFACE[..3]).await.unwrap();
+            tokio::task::yield_now().await;
+            client.write_all(&PREFACE[3..]).await.unwrap();
+        };
+        let (prefix, _) = tokio::join!(read_preface(&mut server, None), read);
+        assert!(is_preface(&prefix.unwrap()));
+
+        client.write_all(b"PRIVATE / HTTP/1.1\r\n").await.unwrap();
+        let prefix = read_preface(&mut server, None).await.unwrap();
+        assert!(!is_preface(&prefix));
+        assert!(prefix.starts_with(b"PRIVATE"));
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_body() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let config = Arc::new(ServerConfig {
+            max_body_size: 16,
+            ..ServerConfig::default()
+        });
+        let mut router = Router::new();
+        router.post("/", || async { "ok" });
+
+        let client = async move {
+            let (mut h2, connection) = h2::client::handshake(client).await.unwrap();
+            tokio::spawn(connection);
+            let request = hyperium_http::Request::post("http://localhost/")
+                .body(())
+                .unwrap();
+            let (response, mut stream) = h2.send_request(request, false).unwrap();
+            stream.send_data(Bytes::from(vec![b'x'; 64]), true).unwrap();
+            response.await.unwrap().status()
+        };
+        tokio::select! {
+            status = client => assert_eq!(status, 413),
+            _ = serve_connection(server, Arc::new(router), config) => panic!("connection closed"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closes_idle_connections() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let config = Arc::new(ServerConfig {
+            keep_alive_timeout: Some(Duration::from_secs(5)),
+            ..ServerConfig::default()
+        });
+        let mut router = Router::new();
+        router.get("/", || async { "ok" });
+
+        let client = async move {
+            let (mut h2, connection) = h2::client::handshake(client).await.unwrap();
+            let connection = tokio::spawn(connection);
+            let request = hyperium_http::Request::get("http://localhost/")
+                .body(())
+                .unwrap();
+            let (response, _) = h2.send_request(request, true).unwrap();
+            let status = response.await.unwrap().status();
+            (status, h2, connection)
+        };
+
+        let started = Instant::now();
+        let served = tokio::time::timeout(Duration::from_secs(60), async {
+            tokio::join!(serve_connection(server, Arc::new(router), config), client)
+        })
+        .await;
+        let (served, (status, _h2, connection)) = served.expect("idle connection was kept open");
+        assert!(served.is_ok());
+        assert_eq!(status, 200);
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_secs(5) && elapsed < Duration::from_secs(6));
+        assert!(connection.await.unwrap().is_ok());
+    }
+}
@@ -55,4 +55,10 @@ impl LifecycleManager {
     pub fn subscribe(&self) -> broadcast::Receiver<ServerState> {
         self.state_sender.subscribe()
     }
-}
\ No newline at end of file
+}
+
+impl Default for LifecycleManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
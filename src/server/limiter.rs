@@ -0,0 +1,126 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+pub struct ConnectionLimiter {
+    semaphore: Arc<Semaphore>,
+    queue: Arc<Semaphore>,
+    max_per_ip: Option<usize>,
+    clients: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+pub struct ClientGuard {
+    ip: IpAddr,
+    clients: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(max_connections: usize, max_per_ip: Option<usize>) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_connections)),
+            queue: Arc::new(Semaphore::new(Semaphore::MAX_PERMITS)),
+            max_per_ip,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn with_queue(mut self, max_queued: usize) -> Self {
+        self.queue = Arc::new(Semaphore::new(max_queued));
+        self
+    }
+
+    pub fn register_client(&self, ip: IpAddr) -> Option<ClientGuard> {
+        let mut clients = self.clients.lock().unwrap();
+        let count = clients.entry(ip).or_insert(0);
+        if let Some(max) = self.max_per_ip
+            && *count >= max
+        {
+            return None;
+        }
+        *count += 1;
+        Some(ClientGuard {
+            ip,
+            clients: Arc::clone(&self.clients),
+        })
+    }
+
+    pub fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        Arc::clone(&self.semaphore).try_acquire_owned().ok()
+    }
+
+    pub fn try_enqueue(&self) -> Option<OwnedSemaphorePermit> {
+        Arc::clone(&self.queue).try_acquire_owned().ok()
+    }
+
+    pub async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        Arc::clone(&self.semaphore).acquire_owned().await.ok()
+    }
+
+    pub fn client_connections(&self, ip: IpAddr) -> usize {
+        self.clients.lock().unwrap().get(&ip).copied().unwrap_or(0)
+    }
+}
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(count) = clients.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                clients.remove(&self.ip);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+
+    #[test]
+    fn per_ip_limit_rejects_extra_connections() {
+        let limiter = ConnectionLimiter::new(10, Some(2));
+        let first = limiter.register_client(CLIENT);
+        let second = limiter.register_client(CLIENT);
+        assert!(first.is_some());
+        assert!(second.is_some());
+        assert!(limiter.register_client(CLIENT).is_none());
+    }
+
+    #[test]
+    fn per_ip_slot_released_on_drop() {
+        let limiter = ConnectionLimiter::new(10, Some(1));
+        let guard = limiter.register_client(CLIENT);
+        assert_eq!(limiter.client_connections(CLIENT), 1);
+        drop(guard);
+        assert_eq!(limiter.client_connections(CLIENT), 0);
+        assert!(limiter.register_client(CLIENT).is_some());
+    }
+
+    #[test]
+    fn try_acquire_fails_when_full() {
+        let limiter = ConnectionLimiter::new(1, None);
+        let permit = limiter.try_acquire();
+        assert!(permit.is_some());
+        assert!(limiter.try_acquire().is_none());
+        drop(permit);
+        assert!(limiter.try_acquire().is_some());
+    }
+
+    #[test]
+    fn queue_is_bounded() {
+        let limiter = ConnectionLimiter::new(1, None).with_queue(1);
+        let _running = limiter.try_acquire();
+        let queued = limiter.try_enqueue();
+        assert!(queued.is_some());
+        assert!(limiter.try_enqueue().is_none());
+        drop(queued);
+        assert!(limiter.try_enqueue().is_some());
+    }
+}
@@ -1,63 +1,95 @@
-use std::{net::SocketAddr, sync::{Arc}};
 pub use crate::server::ServerState;
+use std::{future::Future, net::SocketAddr, sync::Arc, time::Duration};
 use tokio::{
-    io, net::{TcpListener, TcpStream},
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    time::{Instant, error::Elapsed, timeout_at},
 };
+use tokio_rustls::TlsAcceptor;
 use tracing::{error, info, warn};
 
 use crate::{
-    request::request_from_reader,
-    response::{Response},
-    router::router::Router, server::lifecycle::LifecycleManager,
+    extensions::Extensions,
+    request::{
+        read_body, read_head,
+        request::{Request, RequestLineError},
+    },
+    response::{Response, Status, Version},
+    router::router::Router,
+    server::{
+        config::{OverloadBehavior, ServerConfig},
+        http2::{self, Rewind, Upgrade},
+        lifecycle::LifecycleManager,
+        limiter::ConnectionLimiter,
+        tls,
+    },
 };
 
 pub struct Server {
     listener: Option<TcpListener>,
     lifecycle: LifecycleManager,
-    router: Arc<Router>
+    router: Arc<Router>,
+    config: Arc<ServerConfig>,
+    limiter: Arc<ConnectionLimiter>,
+    tls: Option<TlsAcceptor>,
 }
-    
+
 impl Server {
-    
     pub async fn new(router: Router) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_config(router, ServerConfig::default()).await
+    }
+
+    pub async fn with_config(
+        router: Router,
+        config: ServerConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let tls = match &config.tls {
+            Some(tls_config) => Some(tls::acceptor(tls_config, config.http2.enabled)?),
+            None => None,
+        };
         let server = Self {
-          lifecycle: LifecycleManager::new(),
-          router: Arc::new(router),
-        listener: None  
+            lifecycle: LifecycleManager::new(),
+            router: Arc::new(router),
+            limiter: Arc::new(
+                ConnectionLimiter::new(config.max_connections, config.max_connections_per_ip)
+                    .with_queue(config.max_queued_connections),
+            ),
+            config: Arc::new(config),
+            listener: None,
+            tls,
         };
-        
+
         server.boot().await?;
         Ok(server)
     }
-    
+
     async fn boot(&self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Booting server...");
-        
-        
+
         self.lifecycle.transition_to(ServerState::Ready)?;
         info!("Server booted..");
         Ok(())
     }
-    
+
     pub async fn start(&mut self, addr: &str) -> Result<(), Box<dyn std::error::Error>> {
         if self.lifecycle.curent_state() != ServerState::Ready {
             return Err("Server must be in Ready state.".into());
         }
-        self.listener =  Some(TcpListener::bind(addr).await?);
+        self.listener = Some(TcpListener::bind(addr).await?);
         self.lifecycle.transition_to(ServerState::Run)?;
         info!("Server is running on {addr}");
         self.listen().await
     }
 
-     async fn listen(&self)-> Result<(), Box<dyn std::error::Error>> {
+    async fn listen(&self) -> Result<(), Box<dyn std::error::Error>> {
         let listener = self.listener.as_ref().unwrap();
-        
+
         while self.lifecycle.curent_state() == ServerState::Run {
             tokio::select! {
                 accept_result = listener.accept()=> {
                     match accept_result {
                         Ok((socket, addr)) => {
-                            self.handle_connection(socket, addr).await;
+                            self.handle_connection(socket, addr);
                         },
                         Err(e) => error!("Accept Error: {e}")
                     }
@@ -69,46 +101,298 @@ impl Server {
         }
         self.shutdown().await
     }
-    
-    async fn handle_connection(&self, socket: TcpStream, addr: SocketAddr) {
+
+    fn handle_connection(&self, socket: TcpStream, addr: SocketAddr) {
         if self.lifecycle.curent_state() != ServerState::Run {
             error!("Connexion rejected - server is shutting down");
             return;
         }
-        static CONNECTION_SEMAPHORE: tokio::sync::Semaphore = tokio::sync::Semaphore::const_new(10000);
-        
-        let permit = match CONNECTION_SEMAPHORE.acquire().await {
-            Ok(p) =>p,
-            Err(_) => {
-                error!("too many simultaneous connections, {} rejected", addr);
+
+        let client = match self.limiter.register_client(addr.ip()) {
+            Some(client) => client,
+            None => {
+                warn!("too many connections from {}, {} rejected", addr.ip(), addr);
+                self.reject(socket, Status::TOO_MANY_REQUESTS);
                 return;
-            }  
+            }
+        };
+
+        let (permit, queued) = match self.limiter.try_acquire() {
+            Some(permit) => (Some(permit), None),
+            None => match self.config.overload {
+                OverloadBehavior::Queue => match self.limiter.try_enqueue() {
+                    Some(slot) => (None, Some(slot)),
+                    None => {
+                        warn!("connection queue is full, {} rejected", addr);
+                        self.reject(socket, Status::SERVICE_UNAVAILABLE);
+                        return;
+                    }
+                },
+                OverloadBehavior::Reject => {
+                    warn!("too many simultaneous connections, {} rejected", addr);
+                    self.reject(socket, Status::SERVICE_UNAVAILABLE);
+                    return;
+                }
+            },
         };
-        
+
         let router = Arc::clone(&self.router);
-        tokio::spawn(async move{
-            let _permit = permit;
-            
-            match Self::process_connection(socket, router).await {
-                Ok(_) => {
-                info!("Connection ended with success: {}",addr);
+        let config = Arc::clone(&self.config);
+        let limiter = Arc::clone(&self.limiter);
+        let tls = self.tls.clone();
+        tokio::spawn(async move {
+            let _client = client;
+            let _permit = match permit {
+                Some(p) => p,
+                None => match limiter.acquire().await {
+                    Some(p) => p,
+                    None => return,
                 },
+            };
+            drop(queued);
+
+            match Self::serve(socket, router, config, tls).await {
+                Ok(_) => {
+                    info!("Connection ended with success: {}", addr);
+                }
                 Err(e) => {
-                warn!("Erreur on the connection {}: {}",addr, e);  
+                    warn!("Erreur on the connection {}: {}", addr, e);
                 }
             }
         });
     }
-    
-    async fn process_connection( socket: TcpStream, router: Arc<Router>) -> Result<(),Box<dyn std::error::Error>> {
-        let (rd, rw) = io::split(socket);
-        
-        let request = request_from_reader(rd).await.unwrap();
-        let router_guard = router;
-        router_guard.handle_request(request, Response::new(), rw).await;
+
+    fn reject(&self, socket: TcpStream, status: Status) {
+        let mut buffer = [0; 4096];
+        while let Ok(read) = socket.try_read(&mut buffer) {
+            if read < buffer.len() {
+                break;
+            }
+        }
+        if let Ok(response) = Self::overload_response(status, self.config.retry_after).send(None) {
+            let _ = socket.try_write(&response);
+        }
+        if let Ok(socket) = socket.into_std() {
+            let _ = socket.shutdown(std::net::Shutdown::Write);
+        }
+    }
+
+    fn overload_response(status: Status, retry_after: Duration) -> Response {
+        let mut response = Self::error_response(status);
+        response
+            .set_header("Retry-After", &retry_after.as_secs().max(1).to_string())
+            .unwrap();
+        response
+    }
+
+    fn error_response(status: Status) -> Response {
+        let mut response = Response::new();
+        response.status(status);
+        response.set_header("Connection", "close").unwrap();
+        response
+    }
+
+    async fn serve(
+        socket: TcpStream,
+        router: Arc<Router>,
+        config: Arc<ServerConfig>,
+        tls: Option<TlsAcceptor>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let accepted = deadline(config.header_read_timeout);
+        match tls {
+            Some(acceptor) => {
+                let stream = match within(accepted, acceptor.accept(socket)).await {
+                    Ok(stream) => stream?,
+                    Err(_) => return Err("TLS handshake timed out".into()),
+                };
+                if stream.get_ref().1.alpn_protocol() == Some(b"h2") {
+                    http2::serve_connection(stream, router, config).await
+                } else {
+                    let reader = BufReader::new(stream);
+                    Self::process_connection(reader, router, config, accepted, false).await
+                }
+            }
+            None if config.http2.enabled => {
+                let mut socket = socket;
+                let prefix = http2::read_preface(&mut socket, accepted).await?;
+                if prefix.is_empty() {
+                    return Ok(());
+                }
+                let h2 = http2::is_preface(&prefix);
+                let stream = Rewind::new(prefix, socket);
+                if h2 {
+                    http2::serve_connection(stream, router, config).await
+                } else {
+                    let reader = BufReader::new(stream);
+                    Self::process_connection(reader, router, config, accepted, true).await
+                }
+            }
+            None => {
+                let reader = BufReader::new(socket);
+                Self::process_connection(reader, router, config, accepted, false).await
+            }
+        }
+    }
+
+    async fn process_connection<S>(
+        mut reader: BufReader<S>,
+        router: Arc<Router>,
+        config: Arc<ServerConfig>,
+        accepted: Option<Instant>,
+        h2c: bool,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut first = true;
+        let mut buffer = Vec::new();
+
+        loop {
+            let idle_deadline = if first {
+                accepted
+            } else {
+                deadline(config.keep_alive_timeout)
+            };
+            match within(idle_deadline, reader.fill_buf()).await {
+                Err(_) => break,
+                Ok(Err(e)) => return Err(e.into()),
+                Ok(Ok([])) => break,
+                Ok(Ok(_)) => {}
+            }
+
+            let head_deadline = if first {
+                accepted
+            } else {
+                deadline(config.header_read_timeout)
+            };
+            first = false;
+
+            let (request_line, headers) =
+                match within(head_deadline, read_head(&mut reader, &mut buffer)).await {
+                    Ok(Ok(head)) => head,
+                    Ok(Err(e)) => {
+                        let status = match e {
+                            RequestLineError::BadHTTPVersion => Status::HTTP_VERSION_NOT_SUPPORTED,
+                            RequestLineError::LineTooLong => Status::URI_TOO_LONG,
+                            RequestLineError::HeaderFieldsTooLarge => {
+                                Status::REQUEST_HEADER_FIELDS_TOO_LARGE
+                            }
+                            _ => Status::BAD_REQUEST,
+                        };
+                        Self::write_response(&mut reader, Self::error_response(status), &config)
+                            .await?;
+                        return Err(e.into());
+                    }
+                    Err(_) => {
+                        Self::write_response(
+                            &mut reader,
+                            Self::error_response(Status::REQUEST_TIMEOUT),
+                            &config,
+                        )
+                        .await?;
+                        return Err("header read timed out".into());
+                    }
+                };
+
+            let body = match within(
+                deadline(config.body_read_timeout),
+                read_body(&mut reader, &headers, config.max_body_size),
+            )
+            .await
+            {
+                Ok(Ok(body)) => body,
+                Ok(Err(e)) => {
+                    let status = match e {
+                        RequestLineError::BodyTooLarge => Status::CONTENT_TOO_LARGE,
+                        RequestLineError::UnsupportedTransferEncoding => Status::NOT_IMPLEMENTED,
+                        _ => Status::BAD_REQUEST,
+                    };
+                    Self::write_response(&mut reader, Self::error_response(status), &config)
+                        .await?;
+                    return Err(e.into());
+                }
+                Err(_) => {
+                    Self::write_response(
+                        &mut reader,
+                        Self::error_response(Status::REQUEST_TIMEOUT),
+                        &config,
+                    )
+                    .await?;
+                    return Err("body read timed out".into());
+                }
+            };
+
+            let request = Request {
+                request_line,
+                headers,
+                body,
+                extensions: Extensions::new(),
+            };
+            if h2c && let Some(upgrade) = Upgrade::from_request(&request, &config) {
+                let mut response = Response::new();
+                response.status(Status::SWITCHING_PROTOCOLS);
+                response.set_header("Connection", "Upgrade").unwrap();
+                response.set_header("Upgrade", "h2c").unwrap();
+                Self::write_response(&mut reader, response, &config).await?;
+                return http2::serve_upgrade(reader, upgrade, router, config).await;
+            }
+            let keep_alive = request.keep_alive();
+            let head = request.request_line.method == "HEAD";
+            let version = Version::from_request(&request.request_line.http_version);
+            let encoding = request.headers.get_combined("Accept-Encoding");
+
+            let mut response = match within(
+                deadline(config.handler_timeout),
+                router.handle_request(request, Response::new()),
+            )
+            .await
+            {
+                Ok(response) => response,
+                Err(_) => {
+                    warn!("Handler timed out");
+                    let mut response = Response::new();
+                    response.status(Status::GATEWAY_TIMEOUT);
+                    response
+                }
+            };
+            response.version(version);
+            if !keep_alive {
+                response.set_header("Connection", "close").unwrap();
+            } else if version == Version::OneDotZero {
+                response.set_header("Connection", "keep-alive").unwrap();
+            }
+
+            let write = response.write_to(&mut reader, encoding.as_deref(), !head);
+            match within(deadline(config.write_timeout), write).await {
+                Ok(result) => result?,
+                Err(_) => return Err("write timed out".into()),
+            }
+
+            if !keep_alive {
+                break;
+            }
+        }
+
+        let _ = reader.shutdown().await;
         Ok(())
     }
-    
+
+    async fn write_response(
+        wr: &mut (impl AsyncWrite + Unpin),
+        mut response: Response,
+        config: &ServerConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match within(
+            deadline(config.write_timeout),
+            response.write_to(wr, None, true),
+        )
+        .await
+        {
+            Ok(result) => Ok(result?),
+            Err(_) => Err("write timed out".into()),
+        }
+    }
+
     async fn shutdown(&self) -> Result<(), Box<dyn std::error::Error>> {
         self.lifecycle.transition_to(ServerState::Closing)?;
         self.close_connections().await?;
@@ -117,24 +401,281 @@ impl Server {
         info!("Shutting down completes");
         Ok(())
     }
-    
+
     async fn wait_for_shutdown(&self) {
         tokio::signal::ctrl_c().await.expect("Cannot read signal.")
     }
-    
-    
+
     async fn close_connections(&self) -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
     }
-    
-    async fn cleanup(&self)-> Result<(), Box<dyn std::error::Error>> {
+
+    async fn cleanup(&self) -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
     }
 }
 
+pub(crate) fn deadline(timeout: Option<Duration>) -> Option<Instant> {
+    timeout.map(|timeout| Instant::now() + timeout)
+}
 
+pub(crate) async fn within<F: Future>(
+    deadline: Option<Instant>,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    match deadline {
+        Some(deadline) => timeout_at(deadline, future).await,
+        None => Ok(future.await),
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum ServerError {
     PortAlReadyUsed,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::Body;
+    use tokio::io::{AsyncReadExt, DuplexStream, duplex};
+
+    fn config() -> Arc<ServerConfig> {
+        Arc::new(ServerConfig {
+            header_read_timeout: Some(Duration::from_secs(1)),
+            body_read_timeout: Some(Duration::from_secs(2)),
+            handler_timeout: Some(Duration::from_secs(3)),
+            write_timeout: Some(Duration::from_secs(4)),
+            keep_alive_timeout: Some(Duration::from_secs(5)),
+            max_body_size: 1024,
+            ..ServerConfig::default()
+        })
+    }
+
+    fn router() -> Arc<Router> {
+        let mut router = Router::new();
+        router.get("/", || async { "ok" });
+        router.get("/slow", || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            "done"
+        });
+        router.get("/large", || async { vec![b'x'; 64 * 1024] });
+        router.post("/echo", |body: Body| async move { body.to_string_lossy() });
+        Arc::new(router)
+    }
+
+    async fn serve(server: DuplexStream) -> Result<(), String> {
+        let config = config();
+        let accepted = deadline(config.header_read_timeout);
+        Server::process_connection(BufReader::new(server), router(), config, accepted, true)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn read_response(client: &mut DuplexStream) -> String {
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_head_gets_408_within_one_header_timeout() {
+        let (mut client, server) = duplex(1024);
+        let start = Instant::now();
+        let client = async {
+            tokio::time::sleep(Duration::from_millis(900)).await;
+            client.write_all(b"GET / HTTP/1.1\r\nHost").await.unwrap();
+            read_response(&mut client).await
+        };
+        let (result, response) = tokio::join!(serve(server), client);
+        assert_eq!(result.unwrap_err(), "header read timed out");
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_body_gets_408() {
+        let (mut client, server) = duplex(1024);
+        let start = Instant::now();
+        let client = async {
+            client
+                .write_all(b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 10\r\n\r\nabc")
+                .await
+                .unwrap();
+            read_response(&mut client).await
+        };
+        let (result, response) = tokio::join!(serve(server), client);
+        assert_eq!(result.unwrap_err(), "body read timed out");
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_handler_gets_504() {
+        let (mut client, server) = duplex(1024);
+        let start = Instant::now();
+        let client = async {
+            client
+                .write_all(b"GET /slow HTTP/1.1\r\nHost: a\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            read_response(&mut client).await
+        };
+        let (result, response) = tokio::join!(serve(server), client);
+        assert!(result.is_ok());
+        assert!(response.starts_with("HTTP/1.1 504 Gateway Timeout\r\n"));
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn client_that_never_reads_hits_write_timeout() {
+        let (mut client, server) = duplex(1024);
+        let start = Instant::now();
+        let client = async {
+            client
+                .write_all(b"GET /large HTTP/1.1\r\nHost: a\r\n\r\n")
+                .await
+                .unwrap();
+            client
+        };
+        let (result, _client) = tokio::join!(serve(server), client);
+        assert_eq!(result.unwrap_err(), "write timed out");
+        assert_eq!(start.elapsed(), Duration::from_secs(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_keep_alive_connection_is_closed() {
+        let (mut client, server) = duplex(1024);
+        let start = Instant::now();
+        let client = async {
+            client
+                .write_all(b"GET / HTTP/1.1\r\nHost: a\r\n\r\n")
+                .await
+                .unwrap();
+            read_response(&mut client).await
+        };
+        let (result, response) = tokio::join!(serve(server), client);
+        assert!(result.is_ok());
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\nok"));
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+    }
+
+    async fn exchange(request: &[u8]) -> (Result<(), String>, String) {
+        let (mut client, server) = duplex(64 * 1024);
+        let client = async {
+            client.write_all(request).await.unwrap();
+            read_response(&mut client).await
+        };
+        tokio::join!(serve(server), client)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pipelined_chunked_request_is_not_smuggled() {
+        let (result, response) = exchange(
+            b"POST /echo HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\n\r\n\
+            4\r\nGET \r\n6\r\n/large\r\n0\r\n\r\n\
+            GET / HTTP/1.1\r\nHost: a\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        assert!(result.is_ok());
+        let responses: Vec<&str> = response.split("HTTP/1.1 ").skip(1).collect();
+        assert_eq!(responses.len(), 2);
+        assert!(responses[0].starts_with("200 OK\r\n"));
+        assert!(responses[0].ends_with("\r\n\r\nGET /large"));
+        assert!(responses[1].starts_with("200 OK\r\n"));
+        assert!(responses[1].ends_with("\r\n\r\nok"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ambiguous_or_unsupported_framing_is_rejected() {
+        let cases: [(&[u8], &str); 3] = [
+            (
+                b"POST /echo HTTP/1.1\r\nHost: a\r\nContent-Length: 4\r\n\
+                Transfer-Encoding: chunked\r\n\r\n0\r\n\r\nGET / HTTP/1.1\r\n\r\n",
+                "HTTP/1.1 400 Bad Request\r\n",
+            ),
+            (
+                b"POST /echo HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: gzip\r\n\r\n",
+                "HTTP/1.1 501 Not Implemented\r\n",
+            ),
+            (
+                b"POST /echo HTTP/1.1\r\nHost: a\r\nContent-Length: 99999999999\r\n\r\n",
+                "HTTP/1.1 413 Content Too Large\r\n",
+            ),
+        ];
+        for (request, status) in cases {
+            let (result, response) = exchange(request).await;
+            assert!(result.is_err());
+            assert!(response.starts_with(status), "{response}");
+            assert_eq!(response.matches("HTTP/1.1 ").count(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_heads_are_rejected() {
+        let many = format!("GET / HTTP/1.1\r\n{}\r\n", "X-A: 1\r\n".repeat(101));
+        let field = format!("GET / HTTP/1.1\r\nX-A: {}\r\n\r\n", "a".repeat(8192));
+        let target = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(8192));
+        let cases = [
+            (many, "HTTP/1.1 431 Request Header Fields Too Large\r\n"),
+            (field, "HTTP/1.1 431 Request Header Fields Too Large\r\n"),
+            (target, "HTTP/1.1 414 URI Too Long\r\n"),
+        ];
+        for (request, status) in cases {
+            let (result, response) = exchange(request.as_bytes()).await;
+            assert!(result.is_err());
+            assert!(response.starts_with(status), "{response}");
+        }
+    }
+
+    #[tokio::test]
+    async fn upgrades_to_h2c() {
+        let (mut client, server) = duplex(64 * 1024);
+        let client = async move {
+            client
+                .write_all(
+                    b"GET / HTTP/1.1\r\nHost: a\r\nConnection: Upgrade, HTTP2-Settings\r\n\
+                    Upgrade: h2c\r\nHTTP2-Settings: AAMAAABkAAQAAP__\r\n\r\n",
+                )
+                .await
+                .unwrap();
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                head.push(client.read_u8().await.unwrap());
+            }
+            assert!(head.starts_with(b"HTTP/1.1 101 Switching Protocols\r\n"));
+
+            client.write_all(http2::PREFACE).await.unwrap();
+            client
+                .write_all(&[0, 0, 0, 4, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+            let mut status = None;
+            let mut data = Vec::new();
+            loop {
+                let mut header = [0; 9];
+                client.read_exact(&mut header).await.unwrap();
+                let length = u32::from_be_bytes([0, header[0], header[1], header[2]]);
+                let mut payload = vec![0; length as usize];
+                client.read_exact(&mut payload).await.unwrap();
+                if header[5..] != [0, 0, 0, 1] {
+                    continue;
+                }
+                match header[3] {
+                    0x1 => status = payload.first().copied(),
+                    0x0 => data.extend_from_slice(&payload),
+                    _ => {}
+                }
+                if header[4] & 0x1 != 0 {
+                    break;
+                }
+            }
+            (status, data)
+        };
+        let (result, (status, data)) = tokio::join!(serve(server), client);
+        assert!(result.is_ok());
+        assert_eq!(status, Some(0x88));
+        assert_eq!(data, b"ok");
+    }
+}
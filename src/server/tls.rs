@@ -0,0 +1,26 @@
+use std::{fs::File, io::BufReader, sync::Arc};
+
+use tokio_rustls::{TlsAcceptor, rustls};
+
+use crate::server::config::TlsConfig;
+
+pub fn acceptor(
+    config: &TlsConfig,
+    http2: bool,
+) -> Result<TlsAcceptor, Box<dyn std::error::Error>> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(&config.cert_path)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    let key = rustls_pemfile::private_key(&mut BufReader::new(File::open(&config.key_path)?))?
+        .ok_or("No private key found")?;
+
+    let mut server_config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    server_config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
@@ -0,0 +1,6 @@
+pub mod middleware;
+pub mod session;
+pub mod store;
+pub use middleware::SessionMiddleware;
+pub use session::{Session, SessionError, SessionRecord};
+pub use store::{Expired, FileStore, MemoryStore, SessionStore};
@@ -0,0 +1,393 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+use crate::{
+    cookie::{Cookie, SameSite},
+    response::{Response, Status},
+    router::{
+        middleware::{BoxFuture, Middleware, Next},
+        router::Context,
+    },
+    session::{
+        session::{Session, SessionError, SessionRecord},
+        store::SessionStore,
+    },
+};
+
+const ID_LENGTH: usize = 32;
+const SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+
+pub struct SessionMiddleware {
+    store: Arc<dyn SessionStore>,
+    cookie: Cookie,
+    idle_timeout: Option<Duration>,
+    absolute_timeout: Option<Duration>,
+    sweep_interval: Duration,
+    last_sweep: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl SessionMiddleware {
+    pub fn new(store: impl SessionStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            cookie: Cookie::new("session", "")
+                .path("/")
+                .http_only(true)
+                .same_site(SameSite::Lax),
+            idle_timeout: None,
+            absolute_timeout: None,
+            sweep_interval: SWEEP_INTERVAL,
+            last_sweep: Mutex::new(None),
+        }
+    }
+
+    pub fn cookie(mut self, cookie: Cookie) -> Self {
+        self.cookie = cookie;
+        self
+    }
+
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn absolute_timeout(mut self, timeout: Duration) -> Self {
+        self.absolute_timeout = Some(timeout);
+        self
+    }
+
+    pub fn sweep_interval(mut self, interval: Duration) -> Self {
+        self.sweep_interval = interval;
+        self
+    }
+
+    fn expired(&self, record: &SessionRecord, now: DateTime<Utc>) -> bool {
+        expired(record, now, self.idle_timeout, self.absolute_timeout)
+    }
+
+    async fn load(&self, ctx: &Context, now: DateTime<Utc>) -> Result<Session, SessionError> {
+        let cookies = ctx.request.cookies();
+        let Some(id) = cookies.get(&self.cookie.name) else {
+            return Ok(Session::new(None, SessionRecord::new(now)));
+        };
+
+        match self.store.load(id).await {
+            Ok(Some(record)) if !self.expired(&record, now) => {
+                Ok(Session::new(Some(id.to_string()), record))
+            }
+            Ok(Some(_)) => {
+                self.store.delete(id).await?;
+                Ok(Session::new(None, SessionRecord::new(now)))
+            }
+            Ok(None) | Err(SessionError::InvalidId) => {
+                Ok(Session::new(None, SessionRecord::new(now)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn persist(
+        &self,
+        session: &Session,
+        response: &mut Response,
+        now: DateTime<Utc>,
+    ) -> Result<(), SessionError> {
+        let (current, mut record, rotate, destroyed) = {
+            let state = session.lock();
+            (
+                state.id.clone(),
+                state.record.clone(),
+                state.rotate,
+                state.destroyed,
+            )
+        };
+
+        if destroyed {
+            if let Some(id) = current {
+                self.store.delete(&id).await?;
+                let _ = response.remove_cookie(self.cookie.clone());
+            }
+            return Ok(());
+        }
+        if current.is_none() && record.data.is_empty() {
+            return Ok(());
+        }
+
+        let id = match current {
+            Some(id) if !rotate => id,
+            previous => {
+                if let Some(previous) = previous {
+                    self.store.delete(&previous).await?;
+                }
+                let id = generate_id()?;
+                let mut cookie = self.cookie.clone();
+                cookie.value = id.clone();
+                if let Some(timeout) = self.absolute_timeout {
+                    let age = (now - record.created_at).to_std().unwrap_or_default();
+                    cookie.max_age = Some(timeout.saturating_sub(age));
+                }
+                response
+                    .add_cookie(&cookie)
+                    .map_err(|e| SessionError::Store(e.to_string()))?;
+                id
+            }
+        };
+
+        record.last_seen = now;
+        self.store.save(&id, &record).await
+    }
+
+    fn sweep(&self, now: DateTime<Utc>) {
+        if self.idle_timeout.is_none() && self.absolute_timeout.is_none() {
+            return;
+        }
+        {
+            let mut last_sweep = self
+                .last_sweep
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let due = last_sweep.is_none_or(|last| {
+                (now - last)
+                    .to_std()
+                    .is_ok_and(|elapsed| elapsed >= self.sweep_interval)
+            });
+            if !due {
+                return;
+            }
+            *last_sweep = Some(now);
+        }
+
+        let store = Arc::clone(&self.store);
+        let (idle_timeout, absolute_timeout) = (self.idle_timeout, self.absolute_timeout);
+        tokio::spawn(async move {
+            let expired =
+                |record: &SessionRecord| expired(record, now, idle_timeout, absolute_timeout);
+            if let Err(e) = store.sweep(&expired).await {
+                warn!("Session sweep failed: {}", e);
+            }
+        });
+    }
+}
+
+impl Middleware for SessionMiddleware {
+    fn handle<'a>(&'a self, mut ctx: Context, next: Next<'a>) -> BoxFuture<'a, Response> {
+        Box::pin(async move {
+            let now = Utc::now();
+            let session = match self.load(&ctx, now).await {
+                Ok(session) => session,
+                Err(e) => return failure(e),
+            };
+            ctx.insert(session.clone());
+
+            let mut response = next.run(ctx).await;
+            let response = match self.persist(&session, &mut response, now).await {
+                Ok(()) => response,
+                Err(e) => failure(e),
+            };
+            self.sweep(now);
+            response
+        })
+    }
+}
+
+fn expired(
+    record: &SessionRecord,
+    now: DateTime<Utc>,
+    idle_timeout: Option<Duration>,
+    absolute_timeout: Option<Duration>,
+) -> bool {
+    let older_than = |since: DateTime<Utc>, timeout: Option<Duration>| {
+        timeout.is_some_and(|timeout| {
+            (now - since)
+                .to_std()
+                .is_ok_and(|elapsed| elapsed > timeout)
+        })
+    };
+    older_than(record.last_seen, idle_timeout) || older_than(record.created_at, absolute_timeout)
+}
+
+fn generate_id() -> Result<String, SessionError> {
+    let mut bytes = [0u8; ID_LENGTH];
+    getrandom::fill(&mut bytes).map_err(|e| SessionError::Store(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn failure(error: SessionError) -> Response {
+    warn!("Session error: {}", error);
+    let mut response = Response::new();
+    response.status(Status::INTERNAL_SERVER_ERROR);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        request::{request::Request, request_from_reader},
+        router::router::{HandlerError, Router},
+        session::store::MemoryStore,
+    };
+
+    fn router(store: Arc<MemoryStore>) -> Router {
+        let mut router = Router::new();
+        router.add_middleware(
+            SessionMiddleware::new(store)
+                .idle_timeout(Duration::from_secs(60))
+                .absolute_timeout(Duration::from_secs(3600)),
+        );
+        router.post("/login", |ctx: Context| async move {
+            let session = ctx.session().ok_or(HandlerError::InternalError)?;
+            session.rotate_id();
+            session.insert("user", "alice")?;
+            Ok(ctx.response)
+        });
+        router.get("/me", |ctx: Context| async move {
+            let session = ctx.session().ok_or(HandlerError::InternalError)?;
+            let user: String = session.get("user").ok_or(HandlerError::BadRequest)?;
+            let mut response = ctx.response;
+            response.body(user.into_bytes());
+            Ok(response)
+        });
+        router.post("/logout", |ctx: Context| async move {
+            ctx.session().ok_or(HandlerError::InternalError)?.destroy();
+            Ok(ctx.response)
+        });
+        router
+    }
+
+    async fn request(method: &str, target: &str, cookie: Option<&str>) -> Request {
+        let cookie = cookie.map_or(String::new(), |id| format!("Cookie: session={id}\r\n"));
+        let raw = format!("{method} {target} HTTP/1.1\r\n{cookie}\r\n");
+        request_from_reader(raw.as_bytes()).await.unwrap()
+    }
+
+    fn session_cookie(response: &Response) -> Option<String> {
+        let cookie = response.headers().get("set-cookie")?;
+        let (pair, _) = cookie.split_once(';')?;
+        Some(pair.strip_prefix("session=")?.to_string())
+    }
+
+    #[tokio::test]
+    async fn session_survives_between_requests() {
+        let store = Arc::new(MemoryStore::new());
+        let router = router(Arc::clone(&store));
+
+        let anonymous = router
+            .handle_request(request("GET", "/me", None).await, Response::new())
+            .await;
+        assert_eq!(anonymous.status_code(), 400);
+        assert!(store.is_empty());
+
+        let login = router
+            .handle_request(request("POST", "/login", None).await, Response::new())
+            .await;
+        let id = session_cookie(&login).unwrap();
+        assert!(
+            login
+                .headers()
+                .get("set-cookie")
+                .unwrap()
+                .contains("Max-Age=3600; HttpOnly; SameSite=Lax")
+        );
+
+        let me = router
+            .handle_request(request("GET", "/me", Some(&id)).await, Response::new())
+            .await;
+        assert_eq!(me.body_bytes(), b"alice");
+        assert_eq!(session_cookie(&me), None);
+
+        let logout = router
+            .handle_request(request("POST", "/logout", Some(&id)).await, Response::new())
+            .await;
+        assert_eq!(session_cookie(&logout).as_deref(), Some(""));
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn login_rotates_session_id() {
+        let store = Arc::new(MemoryStore::new());
+        let router = router(Arc::clone(&store));
+
+        let first = router
+            .handle_request(request("POST", "/login", None).await, Response::new())
+            .await;
+        let first = session_cookie(&first).unwrap();
+        let second = router
+            .handle_request(
+                request("POST", "/login", Some(&first)).await,
+                Response::new(),
+            )
+            .await;
+        let second = session_cookie(&second).unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(store.len(), 1);
+        assert!(store.load(&first).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn idle_and_absolute_expiry() {
+        let store = Arc::new(MemoryStore::new());
+        let router = router(Arc::clone(&store));
+        let now = Utc::now();
+
+        let mut idle = SessionRecord::new(now - chrono::Duration::seconds(120));
+        idle.data.insert("user".to_string(), "alice".into());
+        store.save("idle", &idle).await.unwrap();
+
+        let mut old = idle.clone();
+        old.created_at = now - chrono::Duration::hours(2);
+        old.last_seen = now;
+        store.save("old", &old).await.unwrap();
+
+        let mut active = idle.clone();
+        active.last_seen = now;
+        store.save("active", &active).await.unwrap();
+
+        for id in ["idle", "old"] {
+            let me = router
+                .handle_request(request("GET", "/me", Some(id)).await, Response::new())
+                .await;
+            assert_eq!(me.status_code(), 400);
+        }
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn expired_sessions_are_swept() {
+        let store = Arc::new(MemoryStore::new());
+        let router = router(Arc::clone(&store));
+        let now = Utc::now();
+
+        let stale = SessionRecord::new(now - chrono::Duration::seconds(120));
+        store.save("stale", &stale).await.unwrap();
+        store
+            .save("active", &SessionRecord::new(now))
+            .await
+            .unwrap();
+
+        router
+            .handle_request(request("GET", "/me", None).await, Response::new())
+            .await;
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(store.len(), 1);
+        assert!(store.load("active").await.unwrap().is_some());
+
+        store.save("stale", &stale).await.unwrap();
+        router
+            .handle_request(request("GET", "/me", None).await, Response::new())
+            .await;
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(store.len(), 2);
+    }
+}
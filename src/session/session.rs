@@ -0,0 +1,148 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub data: HashMap<String, Value>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl SessionRecord {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            data: HashMap::new(),
+            created_at: now,
+            last_seen: now,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct State {
+    pub(crate) id: Option<String>,
+    pub(crate) record: SessionRecord,
+    pub(crate) rotate: bool,
+    pub(crate) destroyed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    state: Arc<Mutex<State>>,
+}
+
+impl Session {
+    pub fn new(id: Option<String>, record: SessionRecord) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                id,
+                record,
+                rotate: false,
+                destroyed: false,
+            })),
+        }
+    }
+
+    pub fn id(&self) -> Option<String> {
+        self.lock().id.clone()
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.lock().record.created_at
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.lock().record.data.get(key)?.clone();
+        serde_json::from_value(value).ok()
+    }
+
+    pub fn insert<T: Serialize>(&self, key: &str, value: T) -> Result<(), SessionError> {
+        let value = serde_json::to_value(value).map_err(|_| SessionError::Serialization)?;
+        self.lock().record.data.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    pub fn remove(&self, key: &str) -> bool {
+        self.lock().record.data.remove(key).is_some()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.lock().record.data.contains_key(key)
+    }
+
+    pub fn clear(&self) {
+        self.lock().record.data.clear();
+    }
+
+    pub fn rotate_id(&self) {
+        self.lock().rotate = true;
+    }
+
+    pub fn destroy(&self) {
+        let mut state = self.lock();
+        state.destroyed = true;
+        state.record.data.clear();
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SessionError {
+    Serialization,
+    InvalidId,
+    Store(String),
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Serialization => write!(f, "Session value cannot be serialized"),
+            Self::InvalidId => write!(f, "Invalid session id"),
+            Self::Store(e) => write!(f, "Session store error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Cart {
+        items: Vec<String>,
+    }
+
+    #[test]
+    fn typed_values_round_trip() {
+        let session = Session::new(None, SessionRecord::new(Utc::now()));
+        session.insert("user_id", 42).unwrap();
+        session
+            .insert(
+                "cart",
+                Cart {
+                    items: vec!["coffee".to_string()],
+                },
+            )
+            .unwrap();
+
+        let handle = session.clone();
+        assert_eq!(handle.get::<u64>("user_id"), Some(42));
+        assert_eq!(handle.get::<String>("user_id"), None);
+        assert_eq!(handle.get::<Cart>("cart").unwrap().items, ["coffee"]);
+        assert!(handle.remove("cart"));
+        assert!(!session.contains("cart"));
+    }
+}
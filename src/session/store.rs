@@ -0,0 +1,285 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crate::{
+    router::middleware::BoxFuture,
+    session::session::{SessionError, SessionRecord},
+};
+
+pub trait SessionStore: Send + Sync {
+    fn load<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SessionRecord>, SessionError>>;
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        record: &'a SessionRecord,
+    ) -> BoxFuture<'a, Result<(), SessionError>>;
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>>;
+    fn sweep<'a>(&'a self, expired: &'a Expired<'a>) -> BoxFuture<'a, Result<usize, SessionError>>;
+}
+
+pub type Expired<'a> = dyn Fn(&SessionRecord) -> bool + Send + Sync + 'a;
+
+impl<S: SessionStore + ?Sized> SessionStore for Arc<S> {
+    fn load<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SessionRecord>, SessionError>> {
+        (**self).load(id)
+    }
+
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        record: &'a SessionRecord,
+    ) -> BoxFuture<'a, Result<(), SessionError>> {
+        (**self).save(id, record)
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>> {
+        (**self).delete(id)
+    }
+
+    fn sweep<'a>(&'a self, expired: &'a Expired<'a>) -> BoxFuture<'a, Result<usize, SessionError>> {
+        (**self).sweep(expired)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    sessions: Mutex<HashMap<String, SessionRecord>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions().is_empty()
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, SessionRecord>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn load<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SessionRecord>, SessionError>> {
+        Box::pin(async move { Ok(self.sessions().get(id).cloned()) })
+    }
+
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        record: &'a SessionRecord,
+    ) -> BoxFuture<'a, Result<(), SessionError>> {
+        Box::pin(async move {
+            self.sessions().insert(id.to_string(), record.clone());
+            Ok(())
+        })
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>> {
+        Box::pin(async move {
+            self.sessions().remove(id);
+            Ok(())
+        })
+    }
+
+    fn sweep<'a>(&'a self, expired: &'a Expired<'a>) -> BoxFuture<'a, Result<usize, SessionError>> {
+        Box::pin(async move {
+            let mut sessions = self.sessions();
+            let before = sessions.len();
+            sessions.retain(|_, record| !expired(record));
+            Ok(before - sessions.len())
+        })
+    }
+}
+
+static TEMPORARY: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    directory: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, SessionError> {
+        let valid = !id.is_empty()
+            && id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+        if !valid {
+            return Err(SessionError::InvalidId);
+        }
+        Ok(self.directory.join(format!("{}.json", id)))
+    }
+}
+
+impl SessionStore for FileStore {
+    fn load<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SessionRecord>, SessionError>> {
+        Box::pin(async move {
+            let path = self.path(id)?;
+            match tokio::fs::read(&path).await {
+                Ok(content) => serde_json::from_slice(&content)
+                    .map(Some)
+                    .map_err(|_| SessionError::Serialization),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(SessionError::Store(e.to_string())),
+            }
+        })
+    }
+
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        record: &'a SessionRecord,
+    ) -> BoxFuture<'a, Result<(), SessionError>> {
+        Box::pin(async move {
+            let path = self.path(id)?;
+            let content = serde_json::to_vec(record).map_err(|_| SessionError::Serialization)?;
+            let temporary = path.with_extension(format!(
+                "json.{}-{}.tmp",
+                std::process::id(),
+                TEMPORARY.fetch_add(1, Ordering::Relaxed)
+            ));
+            tokio::fs::create_dir_all(&self.directory)
+                .await
+                .map_err(|e| SessionError::Store(e.to_string()))?;
+            tokio::fs::write(&temporary, content)
+                .await
+                .map_err(|e| SessionError::Store(e.to_string()))?;
+            tokio::fs::rename(&temporary, &path)
+                .await
+                .map_err(|e| SessionError::Store(e.to_string()))
+        })
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>> {
+        Box::pin(async move {
+            let path = self.path(id)?;
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(SessionError::Store(e.to_string())),
+            }
+        })
+    }
+
+    fn sweep<'a>(&'a self, expired: &'a Expired<'a>) -> BoxFuture<'a, Result<usize, SessionError>> {
+        Box::pin(async move {
+            let mut entries = match tokio::fs::read_dir(&self.directory).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+                Err(e) => return Err(SessionError::Store(e.to_string())),
+            };
+            let mut removed = 0;
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| SessionError::Store(e.to_string()))?
+            {
+                let path = entry.path();
+                if path.extension().is_none_or(|extension| extension != "json") {
+                    continue;
+                }
+                let Ok(content) = tokio::fs::read(&path).await else {
+                    continue;
+                };
+                let Ok(record) = serde_json::from_slice::<SessionRecord>(&content) else {
+                    continue;
+                };
+                if expired(&record) && tokio::fs::remove_file(&path).await.is_ok() {
+                    removed += 1;
+                }
+            }
+            Ok(removed)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    async fn round_trip(store: &dyn SessionStore) {
+        let mut record = SessionRecord::new(Utc::now());
+        record.data.insert("user_id".to_string(), 42.into());
+
+        assert_eq!(store.load("abc").await.unwrap(), None);
+        store.save("abc", &record).await.unwrap();
+        assert_eq!(store.load("abc").await.unwrap(), Some(record));
+        store.delete("abc").await.unwrap();
+        assert_eq!(store.load("abc").await.unwrap(), None);
+        store.delete("abc").await.unwrap();
+    }
+
+    async fn sweeps_expired_records(store: &dyn SessionStore) {
+        let now = Utc::now();
+        let stale = SessionRecord::new(now - chrono::Duration::hours(1));
+        let fresh = SessionRecord::new(now);
+        store.save("stale", &stale).await.unwrap();
+        store.save("fresh", &fresh).await.unwrap();
+
+        let expired = |record: &SessionRecord| record.last_seen < now;
+        assert_eq!(store.sweep(&expired).await.unwrap(), 1);
+        assert_eq!(store.load("stale").await.unwrap(), None);
+        assert_eq!(store.load("fresh").await.unwrap(), Some(fresh));
+        assert_eq!(store.sweep(&expired).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn memory_store_round_trip() {
+        round_trip(&MemoryStore::new()).await;
+        sweeps_expired_records(&MemoryStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn file_store_round_trip() {
+        let directory = std::env::temp_dir().join(format!("sessions-{}", std::process::id()));
+        let store = FileStore::new(&directory);
+        round_trip(&store).await;
+        sweeps_expired_records(&store).await;
+        let leftovers = std::fs::read_dir(&directory)
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+        assert_eq!(
+            store.load("../etc/passwd").await,
+            Err(SessionError::InvalidId)
+        );
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}